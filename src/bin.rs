@@ -23,6 +23,10 @@ use std::sync::mpsc::{self, Sender, Receiver};
 
 fn main() {
     sabi::example5();
+    //sabi::example9();
+    //sabi::example8();
+    //sabi::example7();
+    //sabi::example6();
     //sabi::example4();
     //sabi::example3();
     //sabi::expample2();
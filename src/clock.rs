@@ -1,12 +1,12 @@
+use elements::{*, action::*, shared::*, structures::*, basic::fade, theme::*};
 
-use elements::*;
-
-use conrod::{self, widget, Positionable, Colorable, Widget};
-use std::sync::mpsc::{Sender, Receiver};
+use conrod;
+use std::sync::mpsc::{self, Sender, Receiver};
 use std::sync::{Arc, RwLock};
 use time;
 use std::thread;
 use std;
+use std::i32;
 
 
 
@@ -21,6 +21,16 @@ pub enum ClockMsg {
 
 
 
+/*
+ .o88b. db       .d88b.   .o88b. db   dD     .o88b.  .d88b.  d8888b. d88888b
+d8P  Y8 88      .8P  Y8. d8P  Y8 88 ,8P'    d8P  Y8 .8P  Y8. 88  `8D 88'
+8P      88      88    88 8P      88,8P      8P      88    88 88oobY' 88ooooo
+8b      88      88    88 8b      88`8b      8b      88    88 88`8b   88~~~~~
+Y8b  d8 88booo. `8b  d8' Y8b  d8 88 `88.    Y8b  d8 `8b  d8' 88 `88. 88.
+ `Y88P' Y88888P  `Y88P'   `Y88P' YP   YD     `Y88P'  `Y88P'  88   YD Y88888P
+
+
+*/
 
 
 
@@ -35,6 +45,19 @@ impl ClockCore {
         ClockCore { time, receiver }
     }
 
+    /// runs a new ClockCore in its own thread. The returned time is in seconds
+    /// since the start, the sender stops the thread.
+    pub fn spawn() -> (Arc<RwLock<f64>>, Sender<ClockMsg>) {
+        let time = Arc::new(RwLock::new(0.0));
+        let (sender, receiver): (Sender<ClockMsg>, Receiver<ClockMsg>) = mpsc::channel();
+
+        let core = ClockCore::new(time.clone(), receiver);
+        thread::spawn(move || {
+            core.run();
+        });
+        (time, sender)
+    }
+
     pub fn run(&self) {
         const FPS: f64 = 120.0;
         const DT_NS: u64 = (1.0e9/FPS) as u64;
@@ -80,14 +103,24 @@ impl ClockCore {
 
 
 
+/*
+ .o88b. db       .d88b.   .o88b. db   dD
+d8P  Y8 88      .8P  Y8. d8P  Y8 88 ,8P'
+8P      88      88    88 8P      88,8P
+8b      88      88    88 8b      88`8b
+Y8b  d8 88booo. `8b  d8' Y8b  d8 88 `88.
+ `Y88P' Y88888P  `Y88P'   `Y88P' YP   YD
 
 
+*/
+
 
-/*
 
 
 widget_ids!(
+    #[derive(Clone)]
     struct ClockIds {
+        clock,
         circle_sec,
         line_sec,
         text_sec,
@@ -97,37 +130,262 @@ widget_ids!(
         circle_h,
         line_h,
         text_h,
+        digital,
     }
 );
 
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ClockFace {
+    Analog,
+    Digital,
+}
 
-pub struct Clock {
-    clock_ids: ClockIds,
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ClockMode {
+    /// time of day
+    Time,
+    Stopwatch,
+    /// counts down the given seconds and sends `Finished` when done.
+    Countdown(f64),
+}
 
-    time: Arc<RwLock<f64>>,
-    sender: Sender<ClockMsg>,
 
+pub struct Clock {
+    ids: Option<ClockIds>,
+    parent: Option<conrod::widget::id::Id>,
+    floating: bool,
+
+    id: String,
+    senders: Vec<Sender<ActionMsg>>,
+
+    face: ClockFace,
+    mode: ClockMode,
+    font: Font,
+
+    // optional time source, otherwise the clock is driven by Update messages
+    core: Option<(Arc<RwLock<f64>>, Sender<ClockMsg>)>,
+
+    // shown time in seconds
+    time: f64,
+    // stopwatch and countdown
+    running: bool,
+    start_time: f64,
+    elapsed: f64,
+    laps: Vec<f64>,
+    finished: bool,
+    opacity: f32,
+
+    is_setup: bool,
     frame: Frame<i32>,
     global_center: Vec2<i32>,
+    min_size: Vec2<i32>,
+    max_size: Vec2<i32>,
 }
 
 impl Clock {
-    pub fn new(ui: &mut conrod::Ui, time: Arc<RwLock<f64>>, sender: Sender<ClockMsg>) -> Self {
-        let clock_ids = ClockIds::new(ui.widget_id_generator());
-        Clock { clock_ids, time, sender, frame: Frame{ p0: Vec2{x: 0i32, y: 0i32}, p1: Vec2{x: 100i32, y: 100i32}}, global_center: Vec2{x: 0, y: 0} }
+    pub fn new(face: ClockFace, mode: ClockMode) -> Box<Self> {
+        let mut clock = Clock {
+            ids: None,
+            parent: None,
+            floating: false,
+
+            id: "Clock".to_string(),
+            senders: Vec::new(),
+
+            face,
+            mode,
+            font: Font::new("NotoSans-Regular".to_string(), 42, conrod::color::BLACK),
+
+            core: None,
+
+            time: 0.0,
+            running: false,
+            start_time: 0.0,
+            elapsed: 0.0,
+            laps: Vec::new(),
+            finished: false,
+            opacity: 1.0,
+
+            is_setup: false,
+            frame: Frame::new(),
+            global_center: Vec2::zero(),
+            min_size: Vec2{ x: 100, y: 100 },
+            max_size: Vec2 {x: i32::MAX, y: i32::MAX},
+        };
+        clock.update();
+        Box::new(clock)
     }
-}
 
-impl Element for Clock {
-    fn stop(&self) {
-        let _ = self.sender.send(ClockMsg::Stop);
+    /// use the time of a ClockCore instead of the system time for stopwatch
+    /// and countdown. The time of day always comes from the system clock.
+    /// The core is stopped together with the clock.
+    pub fn with_core(mut self, time: Arc<RwLock<f64>>, sender: Sender<ClockMsg>) -> Box<Self> {
+        self.core = Some((time, sender));
+        self.start_time = self.now();
+        self.update();
+        Box::new(self)
     }
-    fn build_window(&self, ui: &mut conrod::UiCell) {
 
-        let max = self.frame.min_dim();
+    pub fn set_face(&mut self, face: ClockFace) {
+        self.face = face;
+    }
+
+    pub fn set_mode(&mut self, mode: ClockMode) {
+        self.mode = mode;
+        self.reset();
+    }
+
+    /// starts or resumes stopwatch and countdown.
+    pub fn start(&mut self) {
+        if !self.running && !self.finished {
+            self.start_time = self.now();
+            self.running = true;
+        }
+    }
+
+    /// pauses stopwatch and countdown.
+    pub fn pause(&mut self) {
+        if self.running {
+            self.elapsed += self.now() - self.start_time;
+            self.running = false;
+        }
+        self.update();
+    }
+
+    pub fn reset(&mut self) {
+        self.running = false;
+        self.elapsed = 0.0;
+        self.laps.clear();
+        self.finished = false;
+        self.update();
+    }
+
+    /// stores the current lap time and sends it as `Lap(lap number, seconds)`.
+    pub fn lap(&mut self) -> f64 {
+        let t = self.get_elapsed();
+        let n = self.laps.len();
+        self.laps.push(t);
+        self.send(ActionMsgData::Lap(n, t));
+        t
+    }
+
+    pub fn get_laps(&self) -> &Vec<f64> {
+        &self.laps
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn get_elapsed(&self) -> f64 {
+        if self.running {
+            self.elapsed + self.now() - self.start_time
+        } else {
+            self.elapsed
+        }
+    }
+
+    /// currently shown time in seconds.
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+
+    fn now(&self) -> f64 {
+        match self.core {
+            Some((ref time, _)) => *time.read().unwrap(),
+            None => time::precise_time_ns() as f64 * 1e-9,
+        }
+    }
+
+    fn send(&self, msg: ActionMsgData) {
+        for sender in &self.senders {
+            let _ = sender.send(ActionMsg{
+                sender_id: self.id.clone(),
+                msg: msg.clone()
+            });
+        }
+    }
+
+    // not taken from the core, which counts from its start
+    fn time_of_day(&self) -> f64 {
+        let tm = time::now();
+        (tm.tm_hour * 3600 + tm.tm_min * 60 + tm.tm_sec) as f64
+            + tm.tm_nsec as f64 * 1e-9
+    }
+
+    fn update(&mut self) {
+        self.time = match self.mode {
+            ClockMode::Time => self.time_of_day(),
+            ClockMode::Stopwatch => self.get_elapsed(),
+            ClockMode::Countdown(total) => {
+                let rem = total - self.get_elapsed();
+                if rem <= 0.0 {
+                    if !self.finished {
+                        self.finished = true;
+                        self.running = false;
+                        self.elapsed = total;
+                        self.send(ActionMsgData::Finished);
+                    }
+                    0.0
+                } else {
+                    rem
+                }
+            },
+        };
+    }
+
+    fn format_time(&self) -> String {
+        let t = self.time;
+        let h = (t / 3600.0) as u64;
+        let m = (t / 60.0) as u64 % 60;
+        let s = t as u64 % 60;
+        match self.mode {
+            ClockMode::Time => format!("{:02}:{:02}:{:02}", h % 24, m, s),
+            _ => {
+                let cs = (t * 100.0) as u64 % 100;
+                if h > 0 {
+                    format!("{}:{:02}:{:02}.{:02}", h, m, s, cs)
+                } else {
+                    format!("{:02}:{:02}.{:02}", m, s, cs)
+                }
+            },
+        }
+    }
+
+    fn build_digital(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources, ids: &ClockIds) {
+        use conrod::{widget, Positionable, Colorable, Widget};
+
+        let c = self.frame.center() - self.global_center;
+        let text = self.format_time();
+
+        // fit text into frame
+        let size = {
+            let w = self.frame.width() as f64 / (0.6 * text.chars().count() as f64);
+            let h = self.frame.height() as f64 * 0.8;
+            let s = if w < h { w } else { h };
+            let s = s as u32;
+            if s < self.font.get_size() { s } else { self.font.get_size() }
+        };
+
+        let mut label = widget::Text::new(&text)
+            .x_y(c.x as f64, c.y as f64)
+            .color(fade(self.font.get_color(), self.opacity))
+            .font_size(size)
+            .floating(self.floating)
+            .parent(ids.clock);
+        if let Some(fnt) = ressources.font(&self.font.get_font_id()) {
+            label = label.font_id(*fnt);
+        }
+        label.set(ids.digital, ui);
+    }
 
+    fn build_analog(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources, ids: &ClockIds) {
+        use conrod::{widget, Positionable, Colorable, Widget};
+
+        let max = self.frame.min_dim();
         let center = self.frame.center()-self.global_center;
+        let center = [center.x as f64, center.y as f64];
 
         let sec_r = 0.075 * max as f64;
         let min_r = 0.05 * max as f64;
@@ -140,95 +398,160 @@ impl Element for Clock {
         let font_size = (0.05 * max as f64) as u32;
         let linewidth = 0.01 * max as f64;
 
+        let t = self.time;
+
+        let hours = (t/(60.0*60.0)) as u64 % 24;
+        let mins = (t/60.0) as u64 % 60;
+        let secs = t as u64 % 60;
+
+        let fnt = ressources.font(&self.font.get_font_id());
+
+        // (radius of circle, length of hand, period in seconds, value, color, ids)
+        let hands = [
+            (h_r, h_l, 12.0 * 60.0 * 60.0, hours, conrod::color::LIGHT_BROWN,
+                (ids.circle_h, ids.line_h, ids.text_h)),
+            (min_r, min_l, 60.0 * 60.0, mins, conrod::color::LIGHT_GREEN,
+                (ids.circle_min, ids.line_min, ids.text_min)),
+            (sec_r, sec_l, 60.0, secs, conrod::color::LIGHT_BLUE,
+                (ids.circle_sec, ids.line_sec, ids.text_sec)),
+        ];
+
+        for &(r, l, period, value, color, (circle, line, text)) in hands.iter() {
+            let color = fade(color, self.opacity);
+            let style = widget::primitive::shape::Style::outline_styled(
+                widget::primitive::line::Style::solid().thickness(linewidth)
+            );
+
+            let rx = (t / period * 2.0 * std::f64::consts::PI).sin();
+            let ry = (t / period * 2.0 * std::f64::consts::PI).cos();
+
+            widget::Circle::styled(r, style)
+                .x_y(rx*l + center[0], ry*l + center[1])
+                .color(color)
+                .floating(self.floating)
+                .parent(ids.clock)
+                .set(circle, ui);
+
+            widget::Line::abs(center, [rx*(l-r) + center[0], ry*(l-r) + center[1]])
+                .thickness(linewidth)
+                .color(color)
+                .floating(self.floating)
+                .parent(ids.clock)
+                .set(line, ui);
+
+            let mut label = widget::Text::new(&format!("{:2}", value))
+                .x_y(rx*l + center[0], ry*l + center[1])
+                .color(color)
+                .font_size(font_size)
+                .floating(self.floating)
+                .parent(ids.clock);
+            if let Some(fnt) = fnt {
+                label = label.font_id(*fnt);
+            }
+            label.set(text, ui);
+        }
+    }
+}
+
+impl ActionSendable for Clock {
+    fn with_id(mut self, id: String) -> Box<Self> {
+        self.id = id;
+        Box::new(self)
+    }
+    fn with_sender(mut self, sender: Sender<ActionMsg>) -> Box<Self> {
+        self.senders.push(sender);
+        Box::new(self)
+    }
+}
+
+impl Labelable for Clock {
+    fn with_font(mut self, font: Font) -> Box<Self> {
+        self.font = font;
+        Box::new(self)
+    }
+    fn set_font(&mut self, font: Font) {
+        self.font = font;
+    }
+}
+
+impl Element for Clock {
+    fn setup(&mut self, ui: &mut conrod::Ui) {
+        self.ids = Some(ClockIds::new(ui.widget_id_generator()));
+        self.is_setup = true;
+    }
+    fn is_setup(&self) -> bool { self.is_setup }
+
+    fn set_parent_widget(&mut self, parent: conrod::widget::id::Id) {
+        self.parent = Some(parent);
+    }
+    fn set_floating(&mut self, floating: bool) {
+        self.floating = floating;
+    }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        self.font.apply_theme(theme);
+        self.font.apply_style(&theme.style("Clock", &self.id, &[]));
+    }
 
-        let t;
-        {
-            t = *self.time.read().unwrap();
+    fn stop(&mut self) {
+        if let Some((_, ref sender)) = self.core {
+            let _ = sender.send(ClockMsg::Stop);
         }
+    }
+    fn is_animating(&self) -> bool {
+        match self.mode {
+            // only when the shown second changes
+            ClockMode::Time => self.time_of_day() as u64 != self.time as u64,
+            _ => self.running,
+        }
+    }
 
-        let hours = (t/(60.0*60.0)) as u8 % 24;
-        let mins = (t/60.0) as u8 % 60;
-        let secs = t as u8 % 60;
-
-        let style = widget::primitive::shape::Style::outline_styled(
-            widget::primitive::line::Style::solid().thickness(linewidth)
-        );
-
-        let mut rx = (t / (60.0 * 60.0 * 60.0) * 2.0 * std::f64::consts::PI).sin();
-        let mut ry = (t / (60.0 * 60.0 * 60.0) * 2.0 * std::f64::consts::PI).cos();
-        widget::Circle::styled(h_r, style)
-            .x_y(rx * h_l + center.x as f64,ry*h_l + center.y as f64)
-            .color(conrod::color::LIGHT_BROWN)
-            .set(self.clock_ids.circle_h, ui);
-
-        widget::Line::new([center.x as f64, center.y as f64], [rx*(h_l-h_r) + center.x as f64, ry*(h_l-h_r) + center.y as f64])
-            .thickness(linewidth)
-            .color(conrod::color::LIGHT_BROWN)
-            .set(self.clock_ids.line_h, ui);
-
-        widget::Text::new(&format!("{:2}",hours))
-            .x_y(rx*h_l + center.x as f64, ry*h_l + center.y as f64)
-            .color(conrod::color::LIGHT_BROWN)
-            .font_size(font_size)
-            .set(self.clock_ids.text_h, ui);
-
-
-        rx = (t / (60.0 * 60.0) * 2.0 * std::f64::consts::PI).sin();
-        ry = (t / (60.0 * 60.0) * 2.0 * std::f64::consts::PI).cos();
-        widget::Circle::styled(min_r, style)
-            .x_y(rx*min_l + center.x as f64, ry*min_l + center.y as f64)
-            .color(conrod::color::LIGHT_GREEN)
-            .set(self.clock_ids.circle_min, ui);
-
-        widget::Line::new([center.x as f64, center.y as f64], [rx*(min_l-min_r) + center.x as f64, ry*(min_l-min_r) + center.y as f64])
-            .thickness(linewidth)
-            .color(conrod::color::LIGHT_GREEN)
-            .set(self.clock_ids.line_min, ui);
-
-        widget::Text::new(&format!("{:2}",mins))
-            .x_y(rx*min_l + center.x as f64, ry*min_l + center.y as f64)
-            .color(conrod::color::LIGHT_GREEN)
-            .font_size(font_size)
-            .set(self.clock_ids.text_min, ui);
-
-
-        rx = (t / 60.0 * 2.0 * std::f64::consts::PI).sin();
-        ry = (t / 60.0 * 2.0 * std::f64::consts::PI).cos();
-        widget::Circle::styled(sec_r, style)
-            .x_y(rx*sec_l + center.x as f64, ry*sec_l + center.y as f64)
-            .color(conrod::color::LIGHT_BLUE)
-            .set(self.clock_ids.circle_sec, ui);
-
-        widget::Line::new([center.x as f64, center.y as f64], [rx*(sec_l-sec_r) + center.x as f64, ry*(sec_l-sec_r) + center.y as f64])
-            .thickness(linewidth)
-            .color(conrod::color::LIGHT_BLUE)
-            .set(self.clock_ids.line_sec, ui);
-
-        widget::Text::new(&format!("{:2}",secs))
-            .x_y(rx*sec_l + center.x as f64, ry*sec_l + center.y as f64)
-            .color(conrod::color::LIGHT_BLUE)
-            .font_size(font_size)
-            .set(self.clock_ids.text_sec, ui);
+    fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
+        use conrod::{widget, Widget, Positionable};
+
+        if let Some(ref ids) = self.ids {
+            let c = self.frame.center() - self.global_center;
+            let mut rect = widget::Rectangle::fill_with(
+                [self.frame.width() as f64, self.frame.height() as f64],
+                conrod::color::Color::Rgba(0.0,0.0,0.0,0.0)
+            ).x_y(c.x as f64, c.y as f64).floating(self.floating);
+            if let Some(parent) = self.parent {
+                rect = rect.parent(parent);
+            }
+            rect.set(ids.clock, ui);
+
+            match self.face {
+                ClockFace::Analog => self.build_analog(ui, ressources, ids),
+                ClockFace::Digital => self.build_digital(ui, ressources, ids),
+            }
+        }
     }
 
     fn get_frame(&self) -> Frame<i32> { self.frame }
-    fn set_frame(&mut self, frame: Frame<i32>) {
-        //println!("Clock: setting frame {:?}", frame);
+    fn set_frame(&mut self, frame: Frame<i32>, window_center: Vec2<i32>) {
         self.frame = frame;
+        self.global_center = window_center;
     }
 
+    fn set_min_size(&mut self, size: Vec2<i32>) {
+        self.min_size = size;
+    }
     fn get_min_size(&self) -> Vec2<i32> {
-        Vec2{ x: 100, y: 100 }
+        self.min_size
+    }
+    fn set_max_size(&mut self, size: Vec2<i32>) {
+        self.max_size = size;
     }
-    /*
     fn get_max_size(&self) -> Vec2<i32> {
-        Vec2{ x: i32::MAX, y: i32::MAX }
+        self.max_size
     }
-    */
 
-    fn set_window_center(&mut self, center: Vec2<i32>) {
-        self.global_center = center;
+    fn transmit_msg(&mut self, msg: ActionMsg, _stop: bool) {
+        match msg.msg {
+            ActionMsgData::Update => self.update(),
+            _ => ()
+        }
     }
 }
-
-*/
\ No newline at end of file
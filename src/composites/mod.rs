@@ -1,16 +1,20 @@
 
 
 use conrod;
-use elements::{*, action::*, basic::*, shared::*, structures::*};
+use elements::{*, action::*, basic::*, shared::*, structures::*, easing::*, transition::*, spring::*, theme::*, style::*};
 use std::sync::mpsc::Sender;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::i32;
 
 
 
 const DEBUG: bool = false;
 
+// fraction of the overscroll which is shown while dragging beyond the end
+const OVERSCROLL_RESISTANCE: f64 = 0.5;
+
 
 
 
@@ -39,6 +43,8 @@ widget_ids!(
 #[derive(Clone)]
 pub struct Button {
     id: String,
+    // for stylesheet rules like ".warning"
+    classes: Vec<String>,
     senders: Vec<Sender<ActionMsg>>,
 
     //receive_fn: Box<Fn(&mut Element, ActionMsg)>,
@@ -58,14 +64,19 @@ pub struct Button {
     plane_click: Box<Plane>,
     is_hover: bool,
     is_click: bool,
+    // standard, hover and click graphic are taken from the theme
+    themed: [bool; 3],
+    padding: i32,
 
     label: Option<Box<Text>>,
 }
 
 impl Button {
     pub fn new() -> Box<Self> {
+        let theme = Theme::default();
         Box::new(Button {
             id: "Button".to_string(),
+            classes: Vec::new(),
             senders: Vec::new(),
             //receive_fn: rfun,
 
@@ -79,11 +90,13 @@ impl Button {
             parent: None,
             floating: false,
 
-            plane: Plane::new(Graphic::Color(conrod::color::LIGHT_GREY)),
-            plane_hover: Plane::new(Graphic::Color(conrod::color::LIGHT_YELLOW)),
-            plane_click: Plane::new(Graphic::Color(conrod::color::LIGHT_GREEN)),
+            plane: Plane::new(Graphic::Color(theme.button)),
+            plane_hover: Plane::new(Graphic::Color(theme.button_hover)),
+            plane_click: Plane::new(Graphic::Color(theme.button_click)),
             is_hover: false,
             is_click: false,
+            themed: [true; 3],
+            padding: theme.padding,
 
             label: None
         })
@@ -93,23 +106,61 @@ impl Button {
         self.plane = Plane::new(std);
         self.plane_hover = Plane::new(hover);
         self.plane_click = Plane::new(click);
+        self.themed = [false; 3];
         Box::new(self)
     }
 
     pub fn with_graphic(mut self, std: Graphic) -> Box<Self> {
         self.plane = Plane::new(std);
+        self.themed[0] = false;
         Box::new(self)
     }
 
     pub fn with_graphic_hover(mut self, hover: Graphic) -> Box<Self> {
         self.plane_hover = Plane::new(hover);
+        self.themed[1] = false;
         Box::new(self)
     }
 
     pub fn with_graphic_click(mut self, click: Graphic) -> Box<Self> {
         self.plane_click = Plane::new(click);
+        self.themed[2] = false;
+        Box::new(self)
+    }
+
+    /// selects stylesheet rules like ".warning", a button can have several classes.
+    pub fn with_class(mut self, class: String) -> Box<Self> {
+        self.classes.push(class);
         Box::new(self)
     }
+
+    pub fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    pub fn set_graphic(&mut self, std: Graphic) {
+        self.plane.set_graphic(std);
+        self.themed[0] = false;
+    }
+
+    pub fn set_graphic_hover(&mut self, hover: Graphic) {
+        self.plane_hover.set_graphic(hover);
+        self.themed[1] = false;
+    }
+
+    pub fn set_graphic_click(&mut self, click: Graphic) {
+        self.plane_click.set_graphic(click);
+        self.themed[2] = false;
+    }
+
+    // frame of the label inside the padding
+    fn label_frame(&self) -> Frame<i32> {
+        let p = Vec2 {x: self.padding, y: self.padding};
+        Frame {
+            p0: self.frame.p0 + p,
+            p1: self.frame.p1 - p,
+        }
+    }
 }
 
 impl Labelable for Button {
@@ -173,6 +224,34 @@ impl Element for Button {
     fn set_floating(&mut self, floating: bool) {
         self.floating = floating;
     }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.plane.set_opacity(opacity);
+        self.plane_hover.set_opacity(opacity);
+        self.plane_click.set_opacity(opacity);
+        if let Some(ref mut label) = self.label {
+            label.set_opacity(opacity);
+        }
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.style("Button", &self.id, &self.classes);
+        if self.themed[0] {
+            let g = style.get_graphic().unwrap_or(Graphic::Color(theme.button));
+            self.plane.set_graphic(g);
+        }
+        if self.themed[1] {
+            let g = style.get_graphic_hover().unwrap_or(Graphic::Color(theme.button_hover));
+            self.plane_hover.set_graphic(g);
+        }
+        if self.themed[2] {
+            let g = style.get_graphic_click().unwrap_or(Graphic::Color(theme.button_click));
+            self.plane_click.set_graphic(g);
+        }
+        self.padding = style.padding.unwrap_or(theme.padding);
+        if let Some(ref mut label) = self.label {
+            label.set_theme(theme);
+            label.set_style(&style);
+        }
+    }
 
     fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
         use conrod::{widget, Widget, Colorable, Borderable};
@@ -211,8 +290,9 @@ impl Element for Button {
         self.plane_hover.set_frame(frame, window_center);
         self.plane_click.set_frame(frame, window_center);
 
+        let label_frame = self.label_frame();
         if let Some(ref mut label) = self.label {
-            label.set_frame(frame, window_center);
+            label.set_frame(label_frame, window_center);
         }
     }
 
@@ -331,8 +411,16 @@ pub struct Scroll {
     alignment: ScrollAlignment,
     scroll_bar: Box<Socket<Button>>,
     scroll_bar_width: i32,
+    // color and width of the bar are taken from the theme
+    themed_bar: bool,
+    themed_width: bool,
     scroll_position: Rc<RefCell<(f64,f64)>>,
     scroll_trigger: Rc<RefCell<bool>>,
+    transition: Option<LayoutTransition>,
+    bounce: Option<Spring>,
+    overscroll: Option<SpringValue>,
+    bounce_time: Option<u64>,
+    time_source: Arc<TimeSource>,
 
     is_setup: bool,
     frame: Frame<i32>,
@@ -344,13 +432,15 @@ pub struct Scroll {
 impl Scroll {
 
     pub fn new(alignment: ScrollAlignment, id: String, sender: Sender<ActionMsg>) -> Box<Self> {
-        Self::new_with_button(
+        let mut scroll = Self::new_with_button(
              Button::new()
-                .with_graphic(Graphic::Color(conrod::color::LIGHT_BLUE))
+                .with_graphic(Graphic::Color(Theme::default().scroll_bar))
                 .with_id(id)
                 .with_sender(sender),
             alignment
-        )
+        );
+        scroll.themed_bar = true;
+        scroll
     }
 
     pub fn new_with_button(scrollbar_button: Box<Button>, alignment: ScrollAlignment) -> Box<Self> {
@@ -401,9 +491,16 @@ impl Scroll {
             elements: Vec::new(),
             alignment,
             scroll_bar,
-            scroll_bar_width: 15,
+            scroll_bar_width: Theme::default().scroll_bar_width,
+            themed_bar: false,
+            themed_width: true,
             scroll_position,
             scroll_trigger,
+            transition: None,
+            bounce: None,
+            overscroll: None,
+            bounce_time: None,
+            time_source: Arc::new(PreciseTime),
             is_setup: false,
             frame: Frame::new(),
             global_center: Vec2::zero(),
@@ -419,28 +516,103 @@ impl Scroll {
 
     pub fn insert(&mut self, index: usize, element: Box<Element>) {
 
-        if index >= self.elements.len() {
+        let index = if index >= self.elements.len() {
             self.elements.push(element);
+            self.elements.len() - 1
         } else {
             self.elements.insert(index, element);
+            index
+        };
+        if let Some(ref mut tr) = self.transition {
+            tr.element_inserted(index);
         }
 
-        self.rescale_elements();
+        self.animate_elements(Some(index));
         self.is_setup = false;
     }
 
+    /// removes the last element right away, the others move with the transition.
+    /// Use `dismiss` to let it shrink out.
     pub fn pop(&mut self) -> Option<Box<Element>> {
         let el = self.elements.pop();
-        self.rescale_elements();
+        let n = self.elements.len();
+        if let Some(ref mut tr) = self.transition {
+            tr.element_removed(n);
+        }
+        self.animate_elements(None);
         el
     }
 
+    /// removes the element right away, the others move with the transition.
+    /// Use `dismiss` to let it shrink out.
     pub fn remove(&mut self, index: usize) -> Box<Element> {
         let el = self.elements.remove(index);
-        self.rescale_elements();
+        if let Some(ref mut tr) = self.transition {
+            tr.element_removed(index);
+        }
+        self.animate_elements(None);
         el
     }
 
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// removes the element, with a transition it shrinks out before it's dropped.
+    pub fn dismiss(&mut self, index: usize) {
+        let opacity = match self.transition {
+            Some(ref tr) => tr.opacity(index),
+            None => 1.0,
+        };
+        let el = self.remove(index);
+        if let Some(ref mut tr) = self.transition {
+            tr.dismiss(el, opacity);
+        }
+    }
+
+    /// animate pushing, inserting and removing elements.
+    pub fn with_transition(mut self, duration_ms: f64, easing: Easing) -> Box<Self> {
+        self.transition = Some(LayoutTransition::new(duration_ms, easing));
+        Box::new(self)
+    }
+
+    pub fn set_transition(&mut self, transition: Option<LayoutTransition>) {
+        self.transition = transition;
+    }
+
+    /// allow scrolling beyond the ends while dragging, springing back on release.
+    pub fn with_bounce(mut self, spring: Spring) -> Box<Self> {
+        self.bounce = Some(spring);
+        Box::new(self)
+    }
+
+    pub fn set_bounce(&mut self, spring: Option<Spring>) {
+        self.bounce = spring;
+        self.overscroll = None;
+    }
+
+    /// time source of the bounce.
+    pub fn with_time_source(mut self, time_source: Arc<TimeSource>) -> Box<Self> {
+        self.time_source = time_source;
+        Box::new(self)
+    }
+
+    pub fn with_scroll_bar_width(mut self, width: i32) -> Box<Self> {
+        self.scroll_bar_width = width;
+        self.themed_width = false;
+        Box::new(self)
+    }
+
+    pub fn set_scroll_bar_width(&mut self, width: i32) {
+        self.scroll_bar_width = width;
+        self.themed_width = false;
+        self.rescale_elements();
+    }
+
 
     fn get_elements_min_size(&self) -> Vec2<i32> {
         let mut min = Vec2::zero();
@@ -500,42 +672,138 @@ impl Scroll {
     }
 
 
+    // limits the scroll position to the content
+    fn clamp_scroll(&self, sp: f64) -> f64 {
+        let s = self.frame.size();
+        let elmin = self.get_elements_min_size();
+
+        match self.alignment {
+            ScrollAlignment::Horizontal => {
+                let frac = s.x as f64/elmin.x as f64;
+                let sp = if sp < 0.0 { 0.0 } else { sp };
+                let hi = (1.0-frac) * s.x as f64;
+                if sp > hi { hi } else { sp }
+            },
+            ScrollAlignment::Vertical => {
+                let frac = s.y as f64/elmin.y as f64;
+                let sp = if sp > 0.0 { 0.0 } else { sp };
+                let lo = -(1.0-frac) * s.y as f64;
+                if sp < lo { lo } else { sp }
+            },
+        }
+    }
+
+    // shown scroll position, with bounce the overscroll is damped instead of cut
+    fn visible_scroll(&self, sp: f64) -> f64 {
+        let clamped = self.clamp_scroll(sp);
+        match self.bounce {
+            Some(_) => clamped + (sp - clamped) * OVERSCROLL_RESISTANCE,
+            None => clamped,
+        }
+    }
+
+    // springs the scroll position back after overscrolling
+    fn run_bounce(&mut self, update: bool) {
+        let spring = match self.bounce {
+            Some(spring) => spring,
+            None => return,
+        };
+        if *self.scroll_trigger.borrow() {
+            // grabbed again
+            self.overscroll = None;
+            return
+        }
+
+        let (s0, _) = *self.scroll_position.borrow();
+        if self.overscroll.is_none() {
+            let target = self.clamp_scroll(s0);
+            if (s0 - target).abs() > 0.5 {
+                let mut value = SpringValue::new(s0);
+                value.set_target(target);
+                self.overscroll = Some(value);
+                self.bounce_time = None;
+            }
+            return
+        }
+
+        if !update { return }
+        let now = self.time_source.now_ns();
+        let dt = match self.bounce_time {
+            Some(last) => now.saturating_sub(last) as f64 * 1e-9,
+            None => 0.0,
+        };
+        self.bounce_time = Some(now);
+
+        let mut done = false;
+        if let Some(ref mut value) = self.overscroll {
+            value.step(&spring, dt);
+            (*self.scroll_position.borrow_mut()).0 = value.value;
+            done = value.is_at_rest();
+        }
+        if done {
+            self.overscroll = None;
+        }
+        self.rescale_elements();
+    }
+
     fn rescale_elements(&mut self) {
         if DEBUG { println!("rescaling...");}
 
+        let frames = self.layout();
+        match self.transition {
+            Some(ref mut tr) if tr.is_running() => {
+                tr.retarget(frames);
+                tr.apply(&mut self.elements, self.global_center);
+            },
+            _ => {
+                for (el, frame) in self.elements.iter_mut().zip(frames) {
+                    el.set_frame(frame, self.global_center);
+                }
+            },
+        }
+        if DEBUG { println!("... rescaling done.");}
+    }
+
+    // like rescale_elements, but moves the elements with the transition if there is one
+    fn animate_elements(&mut self, inserted: Option<usize>) {
+        if self.transition.is_none() {
+            self.rescale_elements();
+            return
+        }
+        let from = LayoutTransition::current_frames(&self.elements, inserted);
+        let frames = self.layout();
+        if let Some(ref mut tr) = self.transition {
+            tr.start(from, frames);
+            tr.apply(&mut self.elements, self.global_center);
+        }
+    }
+
+    // frames of the elements, also places the scroll bar
+    fn layout(&mut self) -> Vec<Frame<i32>> {
         let n = self.elements.len();
         let s = self.frame.size();
+        let mut frames = Vec::with_capacity(n);
 
         let elmin = self.get_elements_min_size();
 
         let (s0,s1) = *self.scroll_position.borrow();
-        let mut sp = s0 + s1;
+        let sp = self.visible_scroll(s0 + s1);
 
         match self.alignment {
             ScrollAlignment::Horizontal => {
                 let frac = s.x as f64/elmin.x as f64;
                 let bar = (frac * s.x as f64) as i32;
 
-                if sp < 0.0 {
-                    sp = 0.0;
-                    //(*self.scroll_position.borrow_mut()) = (0.0,0.0);
-                }
-                if sp > (1.0-frac) * s.y as f64 {
-                    sp = (1.0-frac) * s.y as f64;
-                    //(*self.scroll_position.borrow_mut()) = (sp,0.0);
-                }
-
                 let scroll = (sp as f64)/(s.x as f64);
                 let delta = (scroll * self.get_elements_min_size().x as f64) as i32;
 
                 let mut xp = 0;
                 for ix in 0..n {
-                    let el = &mut self.elements[ix];
-                    let min = el.get_min_size().x;
-                    el.set_frame(Frame{
+                    let min = self.elements[ix].get_min_size().x;
+                    frames.push(Frame{
                         p0: Vec2{x: delta + xp + self.frame.p0.x, y: self.frame.p0.y},
                         p1: Vec2{x: delta + xp + min + self.frame.p0.x, y: self.frame.p1.y}
-                    }, self.global_center);
+                    });
                     xp += min;
                 }
 
@@ -558,26 +826,16 @@ impl Scroll {
                 let frac = s.y as f64/elmin.y as f64;
                 let bar = (frac * s.y as f64) as i32;
 
-                if sp > 0.0 {
-                    sp = 0.0;
-                    //(*self.scroll_position.borrow_mut()) = (0.0,0.0);
-                }
-                if sp < -(1.0-frac) * s.y as f64 {
-                    sp = -(1.0-frac) * s.y as f64;
-                    //(*self.scroll_position.borrow_mut()) = (sp,0.0);
-                }
-
                 let scroll = (sp as f64)/(s.y as f64);
                 let delta = -(scroll * self.get_elements_min_size().y as f64) as i32;
 
                 let mut yp = 0;
                 for ix in 0..n {
-                    let el = &mut self.elements[ix];
-                    let min = el.get_min_size().y;
-                    el.set_frame(Frame{
+                    let min = self.elements[ix].get_min_size().y;
+                    frames.push(Frame{
                         p0: Vec2{x: self.frame.p0.x, y: delta + self.frame.p1.y - yp - min},
                         p1: Vec2{x: self.frame.p1.x, y: delta + self.frame.p1.y - yp}
-                    }, self.global_center);
+                    });
                     yp += min;
                 }
 
@@ -597,7 +855,7 @@ impl Scroll {
                 );
             },
         }
-        if DEBUG { println!("... rescaling done.");}
+        frames
     }
 }
 
@@ -635,12 +893,40 @@ impl Element for Scroll {
     }
     // TODO maybe implement?
     fn set_floating(&mut self, _floating: bool){}
+    fn set_opacity(&mut self, opacity: f32) {
+        for el in &mut self.elements {
+            el.set_opacity(opacity);
+        }
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        for el in &mut self.elements {
+            el.set_theme(theme);
+        }
+        self.scroll_bar.set_theme(theme);
+
+        let bar = self.scroll_bar.get_element_mut();
+        let style = theme.style("Scroll", &bar.get_id(), &[]);
+        if self.themed_bar {
+            bar.set_graphic(style.get_graphic().unwrap_or(Graphic::Color(theme.scroll_bar)));
+        }
+        if self.themed_width {
+            self.scroll_bar_width = style.width.unwrap_or(theme.scroll_bar_width);
+        }
+    }
 
     fn stop(&mut self) {
         for el in &mut self.elements {
             el.stop();
         }
     }
+    fn is_animating(&self) -> bool {
+        let transition = match self.transition {
+            Some(ref tr) => tr.is_running(),
+            None => false,
+        };
+        transition || self.overscroll.is_some()
+            || self.elements.iter().any(|el| el.is_animating())
+    }
     fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
         use conrod::{widget, Widget, Positionable};
 
@@ -660,6 +946,9 @@ impl Element for Scroll {
         for el in &self.elements {
             el.build_window(ui, ressources);
         }
+        if let Some(ref tr) = self.transition {
+            tr.build_window(ui, ressources);
+        }
 
         if !self.is_inside_area() {
             self.scroll_bar.build_window(ui, ressources);
@@ -711,6 +1000,13 @@ impl Element for Scroll {
         if *self.scroll_trigger.borrow() {
             self.rescale_elements();
         }
+        let update = msg.msg == ActionMsgData::Update;
+        if update {
+            if let Some(ref mut tr) = self.transition {
+                tr.apply(&mut self.elements, self.global_center);
+            }
+        }
+        self.run_bounce(update);
         if !stop {
             match msg.msg {
                 ActionMsgData::Mouse(x,y)
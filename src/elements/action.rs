@@ -2,9 +2,11 @@
 use conrod;
 
 use elements::*;
+use elements::theme::*;
 
 
 use time::precise_time_ns;
+use std::sync::{Arc, RwLock};
 
 
 
@@ -69,6 +71,20 @@ pub enum ActionMsgData {
     F64(f64),
     F32(f32),
     Usize(usize),
+    PlotHover(String, f64, f64),
+    ExportFailed(String, String),
+    Lap(usize, f64),
+    Started,
+    Looped(u32),
+    Finished,
+    FrameChanged(usize),
+    /// play, pause or seek the Sprite or AnimatedImage with the given id.
+    Play(String),
+    Pause(String),
+    Seek(String, usize),
+    ImageLoaded(String),
+    ImageFailed(String, String),
+    Theme(Theme),
     Exit,
 
     Update,
@@ -128,6 +144,52 @@ YP   YP VP   V8P Y888888P YP  YP  YP YP   YP    YP    Y888888P  `Y88P'  VP   V8P
 */
 
 
+/// Source of the time for animations. Replace the precise system time by
+/// a ManualTime to step animations deterministically, e.g. in tests.
+pub trait TimeSource: Send + Sync {
+    fn now_ns(&self) -> u64;
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct PreciseTime;
+
+impl TimeSource for PreciseTime {
+    fn now_ns(&self) -> u64 {
+        precise_time_ns()
+    }
+}
+
+/// time which only moves when told to.
+#[derive(Debug, Clone)]
+pub struct ManualTime {
+    ns: Arc<RwLock<u64>>,
+}
+
+impl ManualTime {
+    pub fn new() -> Self {
+        ManualTime {
+            ns: Arc::new(RwLock::new(0)),
+        }
+    }
+
+    pub fn set_ms(&self, t_ms: f64) {
+        *self.ns.write().unwrap() = (t_ms * 1e6) as u64;
+    }
+
+    pub fn advance_ms(&self, dt_ms: f64) {
+        *self.ns.write().unwrap() += (dt_ms * 1e6) as u64;
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now_ns(&self) -> u64 {
+        *self.ns.read().unwrap()
+    }
+}
+
+
+
+
 widget_ids!(
     #[derive(Clone)]
     struct AnimationIds {
@@ -138,6 +200,8 @@ widget_ids!(
 pub trait Animateable : Element {
     fn animate_size(&mut self, _xy: (Dim,Dim)) {}
     fn animate_position(&mut self, _xy: (Dim,Dim)) {}
+    fn animate_color(&mut self, _color: conrod::Color) {}
+    fn animate_alpha(&mut self, _alpha: f32) {}
     fn start(&mut self){}
     fn run(&mut self){}
     fn reset(&mut self);
@@ -153,9 +217,26 @@ pub trait PositionAnimation {
     fn calc(&self, t: f64, duration: f64) -> (Dim, Dim);
 }
 
+pub trait ColorAnimation {
+    fn calc(&self, t: f64, duration: f64) -> conrod::Color;
+}
+
+pub trait AlphaAnimation {
+    fn calc(&self, t: f64, duration: f64) -> f32;
+}
+
+
+
 
 
 
+/// how often an Animation is played. With ping-pong, each direction counts once.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Repeat {
+    Count(u32),
+    Infinite,
+}
+
 
 
 pub struct Animation {
@@ -163,16 +244,29 @@ pub struct Animation {
     parent: Option<conrod::widget::id::Id>,
     floating: bool,
 
+    id: String,
+    senders: Vec<Sender<ActionMsg>>,
+
     pub element: Box<Animateable>,
 
     size_animation: Option<Box<SizeAnimation>>,
     position_animation: Option<Box<PositionAnimation>>,
+    color_animation: Option<Box<ColorAnimation>>,
+    alpha_animation: Option<Box<AlphaAnimation>>,
     min_size: Vec2<i32>,
     max_size: Vec2<i32>,
 
     duration: f64,
-    start_time: u64,
+    // ns of the time source, negative after seeking further than its time
+    start_time: i64,
     running: bool,
+
+    repeat: Repeat,
+    ping_pong: bool,
+    reverse: bool,
+    paused_at: Option<u64>,
+    loops: u32,
+    time_source: Arc<TimeSource>,
 }
 
 impl Animation {
@@ -181,14 +275,24 @@ impl Animation {
             ids: None,
             parent: None,
             floating: false,
+            id: "Animation".to_string(),
+            senders: Vec::new(),
             element,
             size_animation: None,
             position_animation: None,
+            color_animation: None,
+            alpha_animation: None,
             min_size: Vec2::zero(),
             max_size: Vec2 {x: i32::MAX, y: i32::MAX},
             duration: 100.0,
             start_time: 0,
-            running: false
+            running: false,
+            repeat: Repeat::Count(1),
+            ping_pong: false,
+            reverse: false,
+            paused_at: None,
+            loops: 0,
+            time_source: Arc::new(PreciseTime),
         })
     }
 
@@ -207,13 +311,117 @@ impl Animation {
         Box::new(self)
     }
 
+    pub fn with_color_animation(mut self, animation: Box<ColorAnimation>) -> Box<Self> {
+        self.color_animation = Some(animation);
+        Box::new(self)
+    }
+
+    pub fn with_alpha_animation(mut self, animation: Box<AlphaAnimation>) -> Box<Self> {
+        self.alpha_animation = Some(animation);
+        Box::new(self)
+    }
+
     pub fn with_floating(mut self, floating: bool) -> Box<Self> {
         self.floating = floating;
         Box::new(self)
     }
 
+    pub fn with_time_source(mut self, time_source: Arc<TimeSource>) -> Box<Self> {
+        self.time_source = time_source;
+        Box::new(self)
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat) -> Box<Self> {
+        self.repeat = repeat;
+        Box::new(self)
+    }
+
+    /// every second pass is played backwards.
+    pub fn with_ping_pong(mut self, ping_pong: bool) -> Box<Self> {
+        self.ping_pong = ping_pong;
+        Box::new(self)
+    }
+
+    /// play from the end to the start.
+    pub fn with_reverse(mut self, reverse: bool) -> Box<Self> {
+        self.reverse = reverse;
+        Box::new(self)
+    }
+
+    pub fn set_repeat(&mut self, repeat: Repeat) {
+        self.repeat = repeat;
+    }
+    pub fn set_ping_pong(&mut self, ping_pong: bool) {
+        self.ping_pong = ping_pong;
+    }
+    pub fn set_reverse(&mut self, reverse: bool) {
+        self.reverse = reverse;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn pause(&mut self) {
+        if self.running && self.paused_at.is_none() {
+            self.paused_at = Some(self.time_source.now_ns());
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.start_time += self.time_source.now_ns().saturating_sub(paused_at) as i64;
+        }
+    }
+
+    /// jumps to `t_ms` since the start, counting all passes.
+    pub fn seek(&mut self, t_ms: f64) {
+        let now = match self.paused_at {
+            Some(paused_at) => paused_at,
+            None => self.time_source.now_ns(),
+        };
+        let t = if t_ms < 0.0 { 0 } else { (t_ms * 1e6) as i64 };
+        self.start_time = now as i64 - t;
+        self.loops = (t_ms / self.duration) as u32;
+    }
+
     fn time(&self) -> f64 {
-        (precise_time_ns() - self.start_time) as f64 * 1e-6
+        let now = match self.paused_at {
+            Some(paused_at) => paused_at,
+            None => self.time_source.now_ns(),
+        };
+        // the time source may step backwards
+        let t = now as i64 - self.start_time;
+        if t > 0 { t as f64 * 1e-6 } else { 0.0 }
+    }
+
+    fn send(&self, msg: ActionMsgData) {
+        for sender in &self.senders {
+            let _ = sender.send(ActionMsg{
+                sender_id: self.id.clone(),
+                msg: msg.clone()
+            });
+        }
+    }
+
+    // direction of a pass, true if backwards
+    fn backwards(&self, pass: u32) -> bool {
+        self.reverse ^ (self.ping_pong && pass % 2 == 1)
+    }
+}
+
+
+impl ActionSendable for Animation {
+    fn with_id(mut self, id: String) -> Box<Self> {
+        self.id = id;
+        Box::new(self)
+    }
+    fn with_sender(mut self, sender: Sender<ActionMsg>) -> Box<Self> {
+        self.senders.push(sender);
+        Box::new(self)
     }
 }
 
@@ -225,12 +433,21 @@ impl Animateable for Animation {
     fn animate_position(&mut self, xy: (Dim,Dim)) {
         self.element.animate_position(xy);
     }
+    fn animate_color(&mut self, color: conrod::Color) {
+        self.element.animate_color(color);
+    }
+    fn animate_alpha(&mut self, alpha: f32) {
+        self.element.animate_alpha(alpha);
+    }
 
     fn start(&mut self) {
         // start animation if not already running
         if !self.running {
-            self.start_time = precise_time_ns();
+            self.start_time = self.time_source.now_ns() as i64;
             self.running = true;
+            self.paused_at = None;
+            self.loops = 0;
+            self.send(ActionMsgData::Started);
         }
         // TODO not ideal. need to manage individual floating property
         self.element.set_floating(self.floating);
@@ -238,15 +455,30 @@ impl Animateable for Animation {
     }
 
     fn run(&mut self) {
-        let mut do_reset = false;
+        if !self.running { return }
+
         let tau = self.duration;
-        let t = self.time();
-        let t = if self.running && t < self.duration {
-            t
+        let time = self.time();
+        let pass = (time / tau) as u32;
+
+        let finished = match self.repeat {
+            Repeat::Count(n) => pass >= n,
+            Repeat::Infinite => false,
+        };
+
+        let t = if finished {
+            let last = match self.repeat {
+                Repeat::Count(n) if n > 0 => n - 1,
+                _ => 0,
+            };
+            if self.backwards(last) { 0.0 } else { tau }
         } else {
-            self.running = false;
-            do_reset = true;
-            tau
+            if pass > self.loops {
+                self.loops = pass;
+                self.send(ActionMsgData::Looped(pass));
+            }
+            let t = time - pass as f64 * tau;
+            if self.backwards(pass) { tau - t } else { t }
         };
 
         if let Some(ref f) = self.size_animation {
@@ -255,8 +487,19 @@ impl Animateable for Animation {
         if let Some(ref f) = self.position_animation {
             self.element.animate_position(f.calc(t,tau));
         }
+        if let Some(ref f) = self.color_animation {
+            self.element.animate_color(f.calc(t,tau));
+        }
+        if let Some(ref f) = self.alpha_animation {
+            self.element.animate_alpha(f.calc(t,tau));
+        }
 
-        if do_reset { self.element.reset() }
+        if finished {
+            self.running = false;
+            self.paused_at = None;
+            self.element.reset();
+            self.send(ActionMsgData::Finished);
+        }
     }
 
     fn reset(&mut self) {
@@ -281,6 +524,12 @@ impl Element for Animation {
     fn set_floating(&mut self, floating: bool) {
         self.element.set_floating(floating);
     }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.element.set_opacity(opacity);
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        self.element.set_theme(theme);
+    }
 
     fn set_parent_widget(&mut self, parent: conrod::widget::id::Id) {
         self.parent = Some(parent);
@@ -291,6 +540,9 @@ impl Element for Animation {
         self.element.stop();
         self.running = false;
     }
+    fn is_animating(&self) -> bool {
+        (self.running && self.paused_at.is_none()) || self.element.is_animating()
+    }
     fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
         self.element.build_window(ui, ressources);
     }
@@ -409,6 +661,10 @@ impl<E> Socket<E> where E: Element {
         self.receive = fun;
         Box::new(self)
     }
+
+    pub fn get_element_mut(&mut self) -> &mut E {
+        &mut self.element
+    }
 }
 
 impl<E> Element for Socket<E> where E: Element {
@@ -433,10 +689,19 @@ impl<E> Element for Socket<E> where E: Element {
     fn set_floating(&mut self, floating: bool) {
         self.element.set_floating(floating);
     }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.element.set_opacity(opacity);
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        self.element.set_theme(theme);
+    }
 
     fn stop(&mut self) {
         self.element.stop();
     }
+    fn is_animating(&self) -> bool {
+        self.element.is_animating()
+    }
     fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
         self.element.build_window(ui, ressources);
     }
@@ -469,3 +734,178 @@ impl<E> Element for Socket<E> where E: Element {
         }
     }
 }
+
+
+
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use elements::easing::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::mpsc;
+
+    /// what an animation did to a Probe.
+    #[derive(Debug, Default)]
+    pub struct ProbeLog {
+        pub size: Option<(i32, i32)>,
+        pub position: Option<(i32, i32)>,
+        pub alpha: Option<f32>,
+        pub frame: Option<Frame<i32>>,
+        pub opacity: Option<f32>,
+        pub starts: u32,
+        pub resets: u32,
+    }
+
+    /// element which only records the animated values.
+    pub struct Probe {
+        pub log: Rc<RefCell<ProbeLog>>,
+    }
+
+    impl Probe {
+        pub fn new() -> (Box<Self>, Rc<RefCell<ProbeLog>>) {
+            let log = Rc::new(RefCell::new(ProbeLog::default()));
+            (Box::new(Probe { log: log.clone() }), log)
+        }
+    }
+
+    fn absolute(xy: (Dim, Dim)) -> (i32, i32) {
+        match xy {
+            (Dim::Absolute(x), Dim::Absolute(y)) => (x, y),
+            _ => panic!("relative value {:?}", xy),
+        }
+    }
+
+    impl Animateable for Probe {
+        fn animate_size(&mut self, xy: (Dim,Dim)) {
+            self.log.borrow_mut().size = Some(absolute(xy));
+        }
+        fn animate_position(&mut self, xy: (Dim,Dim)) {
+            self.log.borrow_mut().position = Some(absolute(xy));
+        }
+        fn animate_alpha(&mut self, alpha: f32) {
+            self.log.borrow_mut().alpha = Some(alpha);
+        }
+        fn start(&mut self) {
+            self.log.borrow_mut().starts += 1;
+        }
+        fn reset(&mut self) {
+            self.log.borrow_mut().resets += 1;
+        }
+    }
+
+    impl Element for Probe {
+        fn setup(&mut self, _ui: &mut conrod::Ui) {}
+        fn is_setup(&self) -> bool { true }
+
+        fn set_parent_widget(&mut self, _parent: conrod::widget::id::Id) {}
+        fn set_floating(&mut self, _floating: bool) {}
+        fn set_opacity(&mut self, opacity: f32) {
+            self.log.borrow_mut().opacity = Some(opacity);
+        }
+
+        fn build_window(&self, _ui: &mut conrod::UiCell, _ressources: &WindowRessources) {}
+
+        fn get_frame(&self) -> Frame<i32> {
+            self.log.borrow().frame.unwrap_or(Frame::new())
+        }
+        fn set_frame(&mut self, frame: Frame<i32>, _window_center: Vec2<i32>) {
+            self.log.borrow_mut().frame = Some(frame);
+        }
+
+        fn set_min_size(&mut self, _size: Vec2<i32>) {}
+        fn get_min_size(&self) -> Vec2<i32> { Vec2::zero() }
+        fn set_max_size(&mut self, _size: Vec2<i32>) {}
+        fn get_max_size(&self) -> Vec2<i32> { Vec2::zero() }
+
+        fn transmit_msg(&mut self, _msg: ActionMsg, _stop: bool) {}
+    }
+
+    fn tween(to: i32) -> Box<Tween> {
+        Box::new(Tween::new(
+            (Dim::Absolute(0), Dim::Absolute(0)),
+            (Dim::Absolute(to), Dim::Absolute(2*to)),
+            Easing::Linear
+        ))
+    }
+
+    #[test]
+    fn animation_follows_the_time_source() {
+        let time = ManualTime::new();
+        let (probe, log) = Probe::new();
+        let (sender, receiver) = mpsc::channel();
+        let mut animation = Animation::new(probe)
+            .with_duration(100.0)
+            .with_size_animation(tween(100))
+            .with_time_source(Arc::new(time.clone()))
+            .with_sender(sender);
+
+        animation.start();
+        time.advance_ms(25.0);
+        animation.run();
+        assert_eq!(log.borrow().size, Some((25, 50)));
+        assert!(animation.is_animating());
+
+        time.advance_ms(100.0);
+        animation.run();
+        assert_eq!(log.borrow().size, Some((100, 200)));
+        assert!(!animation.is_running());
+        assert_eq!(log.borrow().resets, 1);
+
+        let msgs: Vec<_> = receiver.try_iter().map(|m| m.msg).collect();
+        assert_eq!(msgs, vec![ActionMsgData::Started, ActionMsgData::Finished]);
+    }
+
+    #[test]
+    fn animation_pauses_and_plays_back_in_ping_pong() {
+        let time = ManualTime::new();
+        let (probe, log) = Probe::new();
+        let mut animation = Animation::new(probe)
+            .with_duration(100.0)
+            .with_size_animation(tween(100))
+            .with_repeat(Repeat::Count(2))
+            .with_ping_pong(true)
+            .with_time_source(Arc::new(time.clone()));
+
+        animation.start();
+        time.advance_ms(30.0);
+        animation.pause();
+        time.advance_ms(1000.0);
+        animation.run();
+        assert_eq!(log.borrow().size, Some((30, 60)));
+        assert!(!animation.is_animating());
+
+        animation.resume();
+        time.advance_ms(120.0);
+        animation.run();
+        // 150 ms, half way back in the second pass
+        assert_eq!(log.borrow().size, Some((50, 100)));
+        assert!(animation.is_running());
+    }
+
+    #[test]
+    fn animation_seeks_past_the_time_source() {
+        let time = ManualTime::new();
+        let (probe, log) = Probe::new();
+        let mut animation = Animation::new(probe)
+            .with_duration(100.0)
+            .with_size_animation(tween(100))
+            .with_repeat(Repeat::Count(10))
+            .with_time_source(Arc::new(time.clone()));
+
+        animation.start();
+        animation.seek(540.0);
+        animation.run();
+        assert_eq!(log.borrow().size, Some((40, 80)));
+
+        // stepping back doesn't underflow
+        time.advance_ms(10.0);
+        animation.seek(0.0);
+        time.set_ms(0.0);
+        animation.pause();
+        animation.resume();
+        animation.run();
+        assert_eq!(log.borrow().size, Some((0, 0)));
+    }
+}
@@ -0,0 +1,297 @@
+use conrod;
+
+use elements::{*, action::*, basic::*};
+use std::sync::Arc;
+use std::sync::mpsc::Sender;
+use std::cell::RefCell;
+
+
+
+
+
+
+
+
+/*
+ .d8b.  d8b   db d888888b .88b  d88.  .d8b.  d888888b d88888b d8888b.    d888888b .88b  d88.  .d8b.   d888b  d88888b
+d8' `8b 888o  88   `88'   88'YbdP`88 d8' `8b `~~88~~' 88'     88  `8D      `88'   88'YbdP`88 d8' `8b 88' Y8b 88'
+88ooo88 88V8o 88    88    88  88  88 88ooo88    88    88ooooo 88   88       88    88  88  88 88ooo88 88      88ooooo
+88~~~88 88 V8o88    88    88  88  88 88~~~88    88    88~~~~~ 88   88       88    88  88  88 88~~~88 88  ooo 88~~~~~
+88   88 88  V888   .88.   88  88  88 88   88    88    88.     88  .8D      .88.   88  88  88 88   88 88. ~8~ 88.
+YP   YP VP   V8P Y888888P YP  YP  YP YP   YP    YP    Y88888P Y8888D'    Y888888P YP  YP  YP YP   YP  Y888P  Y88888P
+
+
+*/
+
+
+
+
+/// Plays an image loaded with `Window::add_animated_image` at its encoded frame
+/// delays. Sends FrameChanged(index) whenever the shown frame changes and
+/// Finished at the end if it doesn't loop. Listens to Play, Pause and Seek
+/// with its id.
+pub struct AnimatedImage {
+    id: String,
+    senders: Vec<Sender<ActionMsg>>,
+
+    plane: Box<Plane>,
+    texture: Texture,
+    // taken from the ressources when building, they change if the file is reloaded
+    delays: RefCell<Vec<f64>>,
+
+    looping: bool,
+    time_source: Arc<TimeSource>,
+
+    playing: bool,
+    // set on the first Update after (re)starting
+    start_time: Option<u64>,
+    // ms played before the last pause or seek
+    offset: f64,
+    current: usize,
+}
+
+impl AnimatedImage {
+    pub fn new(texture: Texture) -> Box<Self> {
+        Box::new(AnimatedImage {
+            id: "AnimatedImage".to_string(),
+            senders: Vec::new(),
+            plane: Plane::new(Graphic::Texture(texture.clone())),
+            texture,
+            delays: RefCell::new(Vec::new()),
+            looping: true,
+            time_source: Arc::new(PreciseTime),
+            playing: true,
+            start_time: None,
+            offset: 0.0,
+            current: 0,
+        })
+    }
+
+    pub fn with_looping(mut self, looping: bool) -> Box<Self> {
+        self.looping = looping;
+        Box::new(self)
+    }
+
+    pub fn with_playing(mut self, playing: bool) -> Box<Self> {
+        self.playing = playing;
+        Box::new(self)
+    }
+
+    pub fn with_time_source(mut self, time_source: Arc<TimeSource>) -> Box<Self> {
+        self.time_source = time_source;
+        Box::new(self)
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    pub fn get_index(&self) -> usize {
+        self.current
+    }
+
+    /// number of frames, 0 before the image was built for the first time.
+    pub fn get_frames(&self) -> usize {
+        self.delays.borrow().len()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// continues playing, a finished animation starts over.
+    pub fn play(&mut self) {
+        if self.playing { return }
+        if !self.looping && self.offset >= self.duration() {
+            self.offset = 0.0;
+            self.set_index(0);
+        }
+        self.start_time = None;
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        if !self.playing { return }
+        self.offset = self.elapsed();
+        self.start_time = None;
+        self.playing = false;
+    }
+
+    /// jumps to the start of the frame `index`, keeps playing if it was.
+    pub fn seek(&mut self, index: usize) {
+        let t: f64 = self.delays.borrow().iter().take(index).sum();
+        self.seek_ms(t);
+    }
+
+    /// jumps to the time `t_ms`, keeps playing if it was.
+    pub fn seek_ms(&mut self, t_ms: f64) {
+        self.offset = if t_ms < 0.0 { 0.0 } else { t_ms };
+        self.start_time = None;
+        let t = self.offset;
+        self.show(t);
+    }
+
+    /// length of one pass in ms.
+    pub fn duration(&self) -> f64 {
+        self.delays.borrow().iter().sum()
+    }
+
+    // ms played so far
+    fn elapsed(&self) -> f64 {
+        match self.start_time {
+            Some(start) => self.offset + (self.time_source.now_ns() - start) as f64 * 1e-6,
+            None => self.offset,
+        }
+    }
+
+    // shows the frame at the time `t` of the animation
+    fn show(&mut self, t: f64) {
+        let index = {
+            let delays = self.delays.borrow();
+            if delays.is_empty() { return }
+            let total: f64 = delays.iter().sum();
+            let mut t = if self.looping && total > 0.0 { t % total } else { t };
+            let mut index = delays.len() - 1;
+            for (i, delay) in delays.iter().enumerate() {
+                if t < *delay {
+                    index = i;
+                    break;
+                }
+                t -= *delay;
+            }
+            index
+        };
+        self.set_index(index);
+    }
+
+    fn set_index(&mut self, index: usize) {
+        if index != self.current {
+            self.current = index;
+            self.send(ActionMsgData::FrameChanged(index));
+        }
+    }
+
+    fn send(&self, msg: ActionMsgData) {
+        for sender in &self.senders {
+            let _ = sender.send(ActionMsg{
+                sender_id: self.id.clone(),
+                msg: msg.clone(),
+            });
+        }
+    }
+}
+
+
+impl ActionSendable for AnimatedImage {
+    fn with_id(mut self, id: String) -> Box<Self> {
+        self.id = id;
+        Box::new(self)
+    }
+    fn with_sender(mut self, sender: Sender<ActionMsg>) -> Box<Self> {
+        self.senders.push(sender);
+        Box::new(self)
+    }
+}
+
+
+impl Animateable for AnimatedImage {
+    fn animate_color(&mut self, color: conrod::Color) {
+        self.plane.animate_color(color);
+    }
+    fn animate_alpha(&mut self, alpha: f32) {
+        self.plane.animate_alpha(alpha);
+    }
+
+    fn run(&mut self) {
+        if !self.playing || self.delays.borrow().is_empty() { return }
+
+        if self.start_time.is_none() {
+            self.start_time = Some(self.time_source.now_ns());
+        }
+        let t = self.elapsed();
+        self.show(t);
+
+        if !self.looping && t >= self.duration() {
+            self.pause();
+            self.send(ActionMsgData::Finished);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.plane.reset();
+    }
+}
+
+
+impl Element for AnimatedImage {
+    fn setup(&mut self, ui: &mut conrod::Ui) {
+        self.plane.setup(ui);
+    }
+    fn is_setup(&self) -> bool {
+        self.plane.is_setup()
+    }
+
+    fn set_parent_widget(&mut self, parent: conrod::widget::id::Id) {
+        self.plane.set_parent_widget(parent);
+    }
+    fn set_floating(&mut self, floating: bool) {
+        self.plane.set_floating(floating);
+    }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.plane.set_opacity(opacity);
+    }
+
+    fn stop(&mut self) {
+        self.pause();
+    }
+    fn is_animating(&self) -> bool {
+        self.playing
+    }
+
+    fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
+        let id = self.texture.get_id();
+        if let Some(delays) = ressources.animation(&id) {
+            if *delays != *self.delays.borrow() {
+                *self.delays.borrow_mut() = delays.clone();
+            }
+        }
+
+        // a reloaded file may have fewer frames
+        let index = self.current.min(self.delays.borrow().len().max(1) - 1);
+        let mut texture = self.texture.clone();
+        texture.set_id(WindowRessources::frame_id(&id, index));
+        self.plane.build_texture(ui, ressources, &texture);
+    }
+
+    fn get_frame(&self) -> Frame<i32> {
+        self.plane.get_frame()
+    }
+    fn set_frame(&mut self, frame: Frame<i32>, window_center: Vec2<i32>) {
+        self.plane.set_frame(frame, window_center);
+    }
+
+    fn set_min_size(&mut self, size: Vec2<i32>) {
+        self.plane.set_min_size(size);
+    }
+    fn get_min_size(&self) -> Vec2<i32> {
+        self.plane.get_min_size()
+    }
+    fn set_max_size(&mut self, size: Vec2<i32>) {
+        self.plane.set_max_size(size);
+    }
+    fn get_max_size(&self) -> Vec2<i32> {
+        self.plane.get_max_size()
+    }
+
+    fn transmit_msg(&mut self, msg: ActionMsg, _stop: bool) {
+        match msg.msg {
+            ActionMsgData::Update => {
+                self.run();
+            },
+            ActionMsgData::Play(ref id) if *id == self.id => self.play(),
+            ActionMsgData::Pause(ref id) if *id == self.id => self.pause(),
+            ActionMsgData::Seek(ref id, index) if *id == self.id => self.seek(index),
+            _ => ()
+        }
+    }
+}
@@ -0,0 +1,73 @@
+/*
+ .d8b.  d888888b db       .d8b.  .d8888.
+d8' `8b `~~88~~' 88      d8' `8b 88'  YP
+88ooo88    88    88      88ooo88 `8bo.
+88~~~88    88    88      88~~~88   `Y8b.
+88   88    88    88booo. 88   88 db   8D
+YP   YP    YP    Y88888P YP   YP `8888Y'
+
+
+*/
+
+
+
+
+// pixels around every image, filled with its edge so that filtering doesn't bleed into the neighbours
+pub const ATLAS_PADDING: u32 = 1;
+
+
+
+/// Packs rectangles of the given sizes into rows ("shelves"), highest first.
+/// Returns the size of the atlas and the position of every rectangle in the
+/// order of `sizes`. Every rectangle has ATLAS_PADDING free pixels around it.
+pub fn pack(sizes: &[(u32, u32)]) -> ((u32, u32), Vec<(u32, u32)>) {
+    let cells: Vec<(u32, u32)> = sizes.iter()
+        .map(|&(w, h)| (w + 2*ATLAS_PADDING, h + 2*ATLAS_PADDING))
+        .collect();
+    let area: u32 = cells.iter().map(|&(w, h)| w * h).sum();
+    let widest = cells.iter().map(|&(w, _)| w).max().unwrap_or(1);
+    // roughly square, power of two widths are friendlier to older GPUs
+    let width = ((area as f64).sqrt().ceil() as u32).max(widest).next_power_of_two();
+
+    let mut order: Vec<usize> = (0..cells.len()).collect();
+    order.sort_by(|&a, &b| cells[b].1.cmp(&cells[a].1));
+
+    let mut positions = vec![(0, 0); cells.len()];
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+    for i in order {
+        let (w, h) = cells[i];
+        if x + w > width {
+            x = 0;
+            y += shelf_height;
+            shelf_height = 0;
+        }
+        positions[i] = (x + ATLAS_PADDING, y + ATLAS_PADDING);
+        x += w;
+        shelf_height = shelf_height.max(h);
+    }
+
+    ((width, (y + shelf_height).max(1)), positions)
+}
+
+/// Copies an image of `size` to `position` of the atlas `data` (4 bytes per pixel,
+/// `atlas_width` pixels per row), `pixel(x, y)` gives its pixels. The edge pixels
+/// are repeated into the padding.
+pub fn blit<F>(data: &mut [u8], atlas_width: u32, position: (u32, u32), size: (u32, u32), pixel: F)
+    where F: Fn(u32, u32) -> [u8; 4]
+{
+    let (x0, y0) = (position.0 as i64, position.1 as i64);
+    let (w, h) = (size.0 as i64, size.1 as i64);
+    if w == 0 || h == 0 { return }
+    let p = ATLAS_PADDING as i64;
+
+    for y in -p..h + p {
+        for x in -p..w + p {
+            let (ax, ay) = (x0 + x, y0 + y);
+            if ax < 0 || ay < 0 || ax >= atlas_width as i64 { continue }
+            let start = (ay as usize * atlas_width as usize + ax as usize) * 4;
+            if start + 4 > data.len() { continue }
+            let edge = pixel(x.max(0).min(w - 1) as u32, y.max(0).min(h - 1) as u32);
+            data[start .. start + 4].copy_from_slice(&edge);
+        }
+    }
+}
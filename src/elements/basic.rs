@@ -4,7 +4,7 @@
 
 use conrod;
 
-use elements::{*, action::*};
+use elements::{*, action::*, theme::*, style::*};
 
 
 
@@ -14,6 +14,15 @@ const DEBUG: bool = false;
 
 
 
+/// multiplies the alpha of a color, used for `set_opacity`.
+pub fn fade(color: conrod::Color, opacity: f32) -> conrod::Color {
+    if opacity >= 1.0 { return color }
+    let conrod::color::Rgba(_,_,_,a) = color.to_rgb();
+    color.alpha(a * opacity)
+}
+
+
+
 
 
 
@@ -115,6 +124,7 @@ widget_ids!(
     #[derive(Clone)]
     struct PlaneIds {
         plane,
+        pieces[],
     }
 );
 
@@ -129,6 +139,10 @@ pub struct Plane {
     frame: Frame<i32>,
 
     graphic: Graphic,
+    // set by animations, reset afterwards
+    color: Option<conrod::Color>,
+    alpha: f32,
+    opacity: f32,
 
     min_size: Vec2<i32>,
     max_size: Vec2<i32>,
@@ -147,6 +161,9 @@ impl Plane {
             frame: Frame::new(),
 
             graphic,
+            color: None,
+            alpha: 1.0,
+            opacity: 1.0,
             min_size: Vec2::zero(),
             max_size: Vec2 {x: i32::MAX, y: i32::MAX},
         })
@@ -155,7 +172,7 @@ impl Plane {
     fn build_textured(
         &self, 
         _ui: &mut conrod::UiCell, 
-        _ressources: &WindowRessources, 
+        ressources: &WindowRessources, 
         texture: (u32,u32,conrod::image::Id),
         texture_properties: &Texture
     ) {
@@ -163,24 +180,79 @@ impl Plane {
 
         if DEBUG { println!("building textured plane with image id {:?}", texture);}
         let c = self.frame.center()-self.global_center;
+        let ids = match self.ids {
+            Some(ref ids) => ids,
+            None => return,
+        };
 
         if DEBUG { println!("creating plane image...");}
-        let mut img = widget::primitive::image::Image::new(texture.2)
-            .source_rectangle(texture_properties.get_cut(
-                self.frame.width() as u32, self.frame.height() as u32, texture.0, texture.1
-            ))
-            .floating(self.floating)
-            .x_y(c.x as f64, c.y as f64)
-            .w_h(self.frame.width() as f64,self.frame.height() as f64);
-        if let Some(parent) = self.parent {
-            img = img.parent(parent);
-        }
-        if let Some(ref ids) = self.ids {
-            img.set(ids.plane, _ui);
+        let pieces = texture_properties.get_pieces(
+            self.frame.width() as u32, self.frame.height() as u32, texture.0, texture.1
+        );
+        let single = pieces.len() == 1;
+        // images packed into an atlas are a part of a larger texture
+        let (ox, oy) = ressources.image_offset(&texture_properties.get_id());
+        ressources.request_size(&texture_properties.get_id(), self.frame.width() as u32, self.frame.height() as u32);
+        let (ox, oy) = (ox as f64, oy as f64);
+        for (i, (src, dst)) in pieces.into_iter().enumerate() {
+            let src = conrod::position::Rect::from_corners(
+                [src.left() + ox, src.bottom() + oy],
+                [src.right() + ox, src.top() + oy]
+            );
+            let id = if single {
+                ids.plane
+            } else {
+                match ids.pieces.get(i) {
+                    Some(id) => *id,
+                    None => break,
+                }
+            };
+            let (x, y) = dst.xy();
+            let mut img = widget::primitive::image::Image::new(texture.2)
+                .source_rectangle(src)
+                .floating(self.floating)
+                .x_y(c.x as f64 + x, c.y as f64 + y)
+                .w_h(dst.w(), dst.h());
+            if self.alpha * self.opacity < 1.0 {
+                img = img.color(Some(fade(conrod::color::WHITE, self.alpha * self.opacity)));
+            }
+            if let Some(parent) = self.parent {
+                img = img.parent(parent);
+            }
+            img.set(id, _ui);
         }
         if DEBUG { println!("Plane build.");}
     }
 
+    // pieces needed for the current graphic
+    fn max_pieces(&self) -> usize {
+        match self.graphic {
+            Graphic::Texture(ref texture) => texture.max_pieces(),
+            _ => 1,
+        }
+    }
+
+    /// builds the plane with `texture` instead of its own graphic.
+    pub fn build_texture(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources, texture: &Texture) {
+        if let Some(tex) = ressources.image(&texture.get_id()) {
+            self.build_textured(ui, ressources, *tex, texture);
+        } else if let Some(placeholder) = texture.get_placeholder() {
+            self.build_graphic(ui, ressources, placeholder);
+        }
+    }
+
+    fn build_graphic(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources, graphic: &Graphic) {
+        match *graphic {
+            Graphic::Texture(ref texture) => {
+                self.build_texture(ui, ressources, texture);
+            },
+            Graphic::Color(color) => {
+                self.build_flat(ui, ressources, color)
+            },
+            Graphic::None => ()
+        }
+    }
+
     fn build_flat(&self, ui: &mut conrod::UiCell, _ressources: &WindowRessources, color: conrod::Color) {
         use conrod::{Positionable, Widget};
 
@@ -189,9 +261,10 @@ impl Plane {
             let c = self.frame.center()-self.global_center;
 
             if DEBUG { println!("creating plane color...");}
+            let color = self.color.unwrap_or(color);
             let mut rect = conrod::widget::Rectangle::fill_with(
                 [self.frame.width() as f64, self.frame.height() as f64],
-                color
+                fade(color, self.alpha * self.opacity)
             ).x_y(c.x as f64, c.y as f64);
             if let Some(parent) = self.parent {
                 rect = rect.parent(parent);
@@ -210,12 +283,37 @@ impl Graphicable for Plane {
     }
     fn set_graphic(&mut self, fg: Graphic) {
         self.graphic = fg;
+        // more pieces need more widget ids
+        let pieces = self.max_pieces();
+        if let Some(ref ids) = self.ids {
+            if pieces > 1 && ids.pieces.len() < pieces {
+                self.is_setup = false;
+            }
+        }
+    }
+}
+
+impl Animateable for Plane {
+    fn animate_color(&mut self, color: conrod::Color) {
+        self.color = Some(color);
+    }
+    fn animate_alpha(&mut self, alpha: f32) {
+        self.alpha = alpha;
+    }
+    fn reset(&mut self) {
+        self.color = None;
+        self.alpha = 1.0;
     }
 }
 
 impl Element for Plane {
     fn setup(&mut self, ui: &mut conrod::Ui) {
-        self.ids = Some(PlaneIds::new(ui.widget_id_generator()));
+        let mut ids = PlaneIds::new(ui.widget_id_generator());
+        let pieces = self.max_pieces();
+        if pieces > 1 {
+            ids.pieces.resize(pieces, &mut ui.widget_id_generator());
+        }
+        self.ids = Some(ids);
         self.is_setup = true;
     }
     fn is_setup(&self) -> bool { self.is_setup }
@@ -226,20 +324,12 @@ impl Element for Plane {
     fn set_floating(&mut self, floating: bool) {
         self.floating = floating;
     }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
 
     fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
-        match self.graphic {
-            Graphic::Texture(ref texture) => {
-                if let Some(tex) = ressources.image(&texture.get_id()) {
-                    self.build_textured(ui, ressources, *tex, &texture);
-                    return;
-                };
-            },
-            Graphic::Color(color) => {
-                self.build_flat(ui, ressources, color)
-            },
-            Graphic::None => ()
-        }
+        self.build_graphic(ui, ressources, &self.graphic);
     }
 
     fn get_frame(&self) -> Frame<i32> {
@@ -303,6 +393,13 @@ widget_ids!(
 #[derive(Clone)]
 pub struct Text {
     font: Font,
+    // only used to select stylesheet rules
+    id: String,
+    classes: Vec<String>,
+    // set by animations, reset afterwards
+    color: Option<conrod::Color>,
+    alpha: f32,
+    opacity: f32,
 
     is_setup: bool,
     frame: Frame<i32>,
@@ -320,6 +417,11 @@ impl Text {
     pub fn new(font: Font) -> Box<Self> {
         Box::new(Text {
             font,
+            id: String::new(),
+            classes: Vec::new(),
+            color: None,
+            alpha: 1.0,
+            opacity: 1.0,
             is_setup: false,
             frame: Frame::new(),
             global_center: Vec2::zero(),
@@ -331,6 +433,36 @@ impl Text {
             floating: false,
         })
     }
+
+    /// selects stylesheet rules like "Text#Title".
+    pub fn with_id(mut self, id: String) -> Box<Self> {
+        self.id = id;
+        Box::new(self)
+    }
+
+    /// selects stylesheet rules like "Text.caption".
+    pub fn with_class(mut self, class: String) -> Box<Self> {
+        self.classes.push(class);
+        Box::new(self)
+    }
+
+    /// styles a themed font, e.g. with the style of the button showing the text.
+    pub fn set_style(&mut self, style: &Style) {
+        self.font.apply_style(style);
+    }
+}
+
+impl Animateable for Text {
+    fn animate_color(&mut self, color: conrod::Color) {
+        self.color = Some(color);
+    }
+    fn animate_alpha(&mut self, alpha: f32) {
+        self.alpha = alpha;
+    }
+    fn reset(&mut self) {
+        self.color = None;
+        self.alpha = 1.0;
+    }
 }
 
 impl Element for Text {
@@ -348,6 +480,13 @@ impl Element for Text {
     fn set_floating(&mut self, floating: bool) {
         self.floating = floating;
     }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        self.font.apply_theme(theme);
+        self.font.apply_style(&theme.style("Text", &self.id, &self.classes));
+    }
 
     fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
         use conrod::{widget, Positionable, Colorable, Widget};
@@ -358,7 +497,10 @@ impl Element for Text {
             let text = self.font.get_text();
             let mut label = widget::Text::new(&text)
                 .x_y(c.x as f64, c.y as f64)
-                .color(self.font.get_color())
+                .color(fade(
+                    self.color.unwrap_or(self.font.get_color()),
+                    self.alpha * self.opacity
+                ))
                 .font_size(self.font.get_size())
                 .floating(self.floating);
 
@@ -0,0 +1,483 @@
+use conrod;
+
+use elements::{*, action::*, basic::fade, theme::*};
+use std::i32;
+
+
+
+
+
+const DEBUG: bool = false;
+
+// number of line segments for arcs
+const ARC_RESOLUTION: usize = 64;
+
+
+
+
+
+
+/*
+.d8888. db   db  .d8b.  d8888b. d88888b
+88'  YP 88   88 d8' `8b 88  `8D 88'
+`8bo.   88ooo88 88ooo88 88oodD' 88ooooo
+  `Y8b. 88~~~88 88~~~88 88~~~   88~~~~~
+db   8D 88   88 88   88 88      88.
+`8888Y' YP   YP YP   YP 88      Y88888P
+
+
+*/
+
+
+
+#[derive(Debug, Copy, Clone)]
+pub struct ShapeStyle {
+    stroke: Option<conrod::Color>,
+    stroke_width: f64,
+    fill: Option<conrod::Color>,
+}
+
+impl ShapeStyle {
+    pub fn stroke(color: conrod::Color, width: f64) -> Self {
+        ShapeStyle {
+            stroke: Some(color),
+            stroke_width: width,
+            fill: None,
+        }
+    }
+
+    pub fn fill(color: conrod::Color) -> Self {
+        ShapeStyle {
+            stroke: None,
+            stroke_width: 1.0,
+            fill: Some(color),
+        }
+    }
+
+    pub fn with_stroke(mut self, color: conrod::Color, width: f64) -> Self {
+        self.stroke = Some(color);
+        self.stroke_width = width;
+        self
+    }
+
+    pub fn with_fill(mut self, color: conrod::Color) -> Self {
+        self.fill = Some(color);
+        self
+    }
+}
+
+
+/// all coordinates are local, see `Canvas::with_view`.
+/// Angles are in radians, counter-clockwise starting at the positive x-axis.
+#[derive(Debug, Clone)]
+pub enum Shape {
+    Line(Vec2<f64>, Vec2<f64>, ShapeStyle),
+    Polyline(Vec<Vec2<f64>>, ShapeStyle),
+    /// center, radius
+    Circle(Vec2<f64>, f64, ShapeStyle),
+    /// center, width, height
+    Ellipse(Vec2<f64>, f64, f64, ShapeStyle),
+    /// center, radius, start angle, end angle. Filled arcs are drawn as pie slices.
+    Arc(Vec2<f64>, f64, f64, f64, ShapeStyle),
+    Polygon(Vec<Vec2<f64>>, ShapeStyle),
+    /// frame, corner radius
+    RoundedRectangle(Frame<f64>, f64, ShapeStyle),
+    /// centered at the given position
+    Text(Vec2<f64>, Font),
+}
+
+
+
+
+
+
+
+
+
+
+
+/*
+ .o88b.  .d8b.  d8b   db db    db  .d8b.  .d8888.
+d8P  Y8 d8' `8b 888o  88 88    88 d8' `8b 88'  YP
+8P      88ooo88 88V8o 88 Y8    8P 88ooo88 `8bo.
+8b      88~~~88 88 V8o88 `8b  d8' 88~~~88   `Y8b.
+Y8b  d8 88   88 88  V888  `8bd8'  88   88 db   8D
+ `Y88P' YP   YP VP   V8P    YP    YP   YP `8888Y'
+
+
+*/
+
+
+
+widget_ids!(
+    #[derive(Clone)]
+    struct CanvasIds {
+        canvas,
+        fills[],
+        strokes[],
+    }
+);
+
+
+pub struct Canvas {
+    ids: Option<CanvasIds>,
+    parent: Option<conrod::widget::id::Id>,
+    floating: bool,
+
+    shapes: Vec<Shape>,
+    view: Option<Frame<f64>>,
+    background: conrod::Color,
+    opacity: f32,
+
+    is_setup: bool,
+    frame: Frame<i32>,
+    global_center: Vec2<i32>,
+    min_size: Vec2<i32>,
+    max_size: Vec2<i32>,
+}
+
+impl Canvas {
+    pub fn new() -> Box<Self> {
+        Box::new(Canvas {
+            ids: None,
+            parent: None,
+            floating: false,
+
+            shapes: Vec::new(),
+            view: None,
+            background: conrod::color::Color::Rgba(0.0,0.0,0.0,0.0),
+            opacity: 1.0,
+
+            is_setup: false,
+            frame: Frame::new(),
+            global_center: Vec2::zero(),
+            min_size: Vec2::zero(),
+            max_size: Vec2 {x: i32::MAX, y: i32::MAX},
+        })
+    }
+
+    /// local coordinates of the canvas corners. Without a view,
+    /// local coordinates are pixel from the bottom left of the frame.
+    pub fn with_view(mut self, view: Frame<f64>) -> Box<Self> {
+        self.view = Some(view);
+        Box::new(self)
+    }
+
+    pub fn with_background(mut self, color: conrod::Color) -> Box<Self> {
+        self.background = color;
+        Box::new(self)
+    }
+
+    pub fn with_shape(mut self, shape: Shape) -> Box<Self> {
+        self.shapes.push(shape);
+        Box::new(self)
+    }
+
+    pub fn with_shapes(mut self, shapes: Vec<Shape>) -> Box<Self> {
+        self.shapes = shapes;
+        Box::new(self)
+    }
+
+    pub fn set_view(&mut self, view: Option<Frame<f64>>) {
+        self.view = view;
+    }
+
+    pub fn push(&mut self, shape: Shape) {
+        self.shapes.push(shape);
+    }
+
+    pub fn set_shapes(&mut self, shapes: Vec<Shape>) {
+        self.shapes = shapes;
+    }
+
+    pub fn pop(&mut self) -> Option<Shape> {
+        self.shapes.pop()
+    }
+
+    pub fn clear(&mut self) {
+        self.shapes.clear();
+    }
+
+    pub fn shapes(&self) -> &Vec<Shape> {
+        &self.shapes
+    }
+
+    pub fn shapes_mut(&mut self) -> &mut Vec<Shape> {
+        &mut self.shapes
+    }
+
+    /// local coordinates of a point given in window pixel, e.g. of a mouse click.
+    pub fn to_local(&self, x: f64, y: f64) -> Vec2<f64> {
+        let origin = match self.view {
+            Some(view) => view.p0,
+            None => Vec2::zero(),
+        };
+        let s = self.scale();
+        Vec2{
+            x: (x - self.frame.p0.x as f64) / s.x + origin.x,
+            y: (y - self.frame.p0.y as f64) / s.y + origin.y,
+        }
+    }
+
+    // scale from local coordinates to pixel
+    fn scale(&self) -> Vec2<f64> {
+        match self.view {
+            Some(view) => Vec2{
+                x: self.frame.width() as f64 / view.width(),
+                y: self.frame.height() as f64 / view.height(),
+            },
+            None => Vec2{x: 1.0, y: 1.0},
+        }
+    }
+
+    // local coordinates to conrod coordinates
+    fn map(&self, p: Vec2<f64>) -> [f64; 2] {
+        let origin = match self.view {
+            Some(view) => view.p0,
+            None => Vec2::zero(),
+        };
+        let s = self.scale();
+        let p = (p - origin).el_mul(s);
+        [
+            p.x + (self.frame.p0.x - self.global_center.x) as f64,
+            p.y + (self.frame.p0.y - self.global_center.y) as f64,
+        ]
+    }
+
+    fn arc_points(&self, c: Vec2<f64>, r: f64, a0: f64, a1: f64) -> Vec<[f64; 2]> {
+        let mut res = Vec::with_capacity(ARC_RESOLUTION + 1);
+        for n in 0..(ARC_RESOLUTION + 1) {
+            let a = a0 + (a1 - a0) * n as f64 / ARC_RESOLUTION as f64;
+            res.push(self.map(Vec2{x: c.x + r * a.cos(), y: c.y + r * a.sin()}));
+        }
+        res
+    }
+
+    fn build_path(
+        &self, ui: &mut conrod::UiCell, id: conrod::widget::id::Id,
+        points: Vec<[f64; 2]>, style: &ShapeStyle
+    ) {
+        use conrod::{widget, Colorable, Widget};
+
+        if let (Some(color), Some(ref ids)) = (style.stroke, self.ids.as_ref()) {
+            widget::PointPath::abs(points)
+                .color(fade(color, self.opacity))
+                .thickness(style.stroke_width)
+                .parent(ids.canvas)
+                .set(id, ui);
+        }
+    }
+
+    fn build_area(
+        &self, ui: &mut conrod::UiCell, id: conrod::widget::id::Id,
+        points: Vec<[f64; 2]>, style: &ShapeStyle
+    ) {
+        use conrod::{widget, Colorable, Widget};
+
+        if let (Some(color), Some(ref ids)) = (style.fill, self.ids.as_ref()) {
+            widget::Polygon::abs_fill(points)
+                .color(fade(color, self.opacity))
+                .parent(ids.canvas)
+                .set(id, ui);
+        }
+    }
+
+    fn build_shape(
+        &self, ui: &mut conrod::UiCell, ressources: &WindowRessources,
+        shape: &Shape, fill: conrod::widget::id::Id, stroke: conrod::widget::id::Id
+    ) {
+        use conrod::{widget, Colorable, Positionable, Widget};
+
+        let parent = match self.ids {
+            Some(ref ids) => ids.canvas,
+            None => return,
+        };
+        let s = self.scale();
+
+        match *shape {
+            Shape::Line(a, b, ref style) => {
+                self.build_path(ui, stroke, vec![self.map(a), self.map(b)], style);
+            },
+            Shape::Polyline(ref points, ref style) => {
+                let points = points.iter().map(|&p| self.map(p)).collect();
+                self.build_path(ui, stroke, points, style);
+            },
+            Shape::Circle(c, r, ref style) => {
+                self.build_oval(ui, fill, stroke, c, 2.0*r*s.x, 2.0*r*s.y, style);
+            },
+            Shape::Ellipse(c, w, h, ref style) => {
+                self.build_oval(ui, fill, stroke, c, w*s.x, h*s.y, style);
+            },
+            Shape::Arc(c, r, a0, a1, ref style) => {
+                let points = self.arc_points(c, r, a0, a1);
+                if style.fill.is_some() {
+                    let mut area = points.clone();
+                    area.push(self.map(c));
+                    self.build_area(ui, fill, area, style);
+                }
+                self.build_path(ui, stroke, points, style);
+            },
+            Shape::Polygon(ref points, ref style) => {
+                let mut points: Vec<[f64; 2]> = points.iter().map(|&p| self.map(p)).collect();
+                self.build_area(ui, fill, points.clone(), style);
+                if let Some(&first) = points.first() {
+                    points.push(first);
+                }
+                self.build_path(ui, stroke, points, style);
+            },
+            Shape::RoundedRectangle(frame, r, ref style) => {
+                let c = self.map(frame.center());
+                let dim = [frame.width() * s.x, frame.height() * s.y];
+                let r = r * if s.x < s.y { s.x } else { s.y };
+                if let Some(color) = style.fill {
+                    widget::RoundedRectangle::fill(dim, r)
+                        .x_y(c[0], c[1])
+                        .color(fade(color, self.opacity))
+                        .floating(self.floating)
+                        .parent(parent)
+                        .set(fill, ui);
+                }
+                if let Some(color) = style.stroke {
+                    widget::RoundedRectangle::outline_styled(
+                        dim, r,
+                        widget::primitive::line::Style::solid().thickness(style.stroke_width)
+                    ).x_y(c[0], c[1])
+                        .color(fade(color, self.opacity))
+                        .floating(self.floating)
+                        .parent(parent)
+                        .set(stroke, ui);
+                }
+            },
+            Shape::Text(p, ref font) => {
+                let c = self.map(p);
+                let text = font.get_text();
+                let mut label = widget::Text::new(&text)
+                    .x_y(c[0], c[1])
+                    .color(fade(font.get_color(), self.opacity))
+                    .font_size(font.get_size())
+                    .floating(self.floating)
+                    .parent(parent);
+                if let Some(fnt) = ressources.font(&font.get_font_id()) {
+                    label = label.font_id(*fnt);
+                }
+                label.set(fill, ui);
+            },
+        }
+    }
+
+    fn build_oval(
+        &self, ui: &mut conrod::UiCell,
+        fill: conrod::widget::id::Id, stroke: conrod::widget::id::Id,
+        c: Vec2<f64>, w: f64, h: f64, style: &ShapeStyle
+    ) {
+        use conrod::{widget, Colorable, Positionable, Widget};
+
+        let parent = match self.ids {
+            Some(ref ids) => ids.canvas,
+            None => return,
+        };
+        let c = self.map(c);
+
+        if let Some(color) = style.fill {
+            widget::Oval::fill([w, h])
+                .x_y(c[0], c[1])
+                .color(fade(color, self.opacity))
+                .floating(self.floating)
+                .parent(parent)
+                .set(fill, ui);
+        }
+        if let Some(color) = style.stroke {
+            widget::Oval::outline_styled(
+                [w, h],
+                widget::primitive::line::Style::solid().thickness(style.stroke_width)
+            ).x_y(c[0], c[1])
+                .color(fade(color, self.opacity))
+                .floating(self.floating)
+                .parent(parent)
+                .set(stroke, ui);
+        }
+    }
+}
+
+impl Element for Canvas {
+    fn setup(&mut self, ui: &mut conrod::Ui) {
+        // ids of the shapes drawn before are kept, only additional shapes get new ones
+        let mut ids = match self.ids.take() {
+            Some(ids) => ids,
+            None => CanvasIds::new(ui.widget_id_generator()),
+        };
+        if ids.fills.len() < self.shapes.len() {
+            ids.fills.resize(self.shapes.len(), &mut ui.widget_id_generator());
+            ids.strokes.resize(self.shapes.len(), &mut ui.widget_id_generator());
+        }
+        self.ids = Some(ids);
+        self.is_setup = true;
+        if DEBUG { println!("Canvas --- setup()"); }
+    }
+    fn is_setup(&self) -> bool {
+        self.is_setup && self.ids.as_ref().map_or(false, |ids| ids.fills.len() >= self.shapes.len())
+    }
+
+    fn set_parent_widget(&mut self, parent: conrod::widget::id::Id) {
+        self.parent = Some(parent);
+    }
+    fn set_floating(&mut self, floating: bool) {
+        self.floating = floating;
+    }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        for shape in &mut self.shapes {
+            if let Shape::Text(_, ref mut font) = *shape {
+                font.apply_theme(theme);
+            }
+        }
+    }
+
+    fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
+        use conrod::{widget, Widget, Positionable};
+
+        if let Some(ref ids) = self.ids {
+            let c = self.frame.center() - self.global_center;
+            let mut rect = widget::Rectangle::fill_with(
+                [self.frame.width() as f64, self.frame.height() as f64],
+                fade(self.background, self.opacity)
+            ).x_y(c.x as f64, c.y as f64)
+                .floating(self.floating)
+                .crop_kids();
+            if let Some(parent) = self.parent {
+                rect = rect.parent(parent);
+            }
+            rect.set(ids.canvas, ui);
+
+            for (n, shape) in self.shapes.iter().enumerate() {
+                if n >= ids.fills.len() || n >= ids.strokes.len() { break; }
+                self.build_shape(ui, ressources, shape, ids.fills[n], ids.strokes[n]);
+            }
+        }
+    }
+
+    fn get_frame(&self) -> Frame<i32> {
+        self.frame
+    }
+    fn set_frame(&mut self, frame: Frame<i32>, window_center: Vec2<i32>) {
+        self.frame = frame;
+        self.global_center = window_center;
+    }
+
+    fn set_min_size(&mut self, size: Vec2<i32>) {
+        self.min_size = size;
+    }
+    fn get_min_size(&self) -> Vec2<i32> {
+        self.min_size
+    }
+    fn set_max_size(&mut self, size: Vec2<i32>) {
+        self.max_size = size;
+    }
+    fn get_max_size(&self) -> Vec2<i32> {
+        self.max_size
+    }
+
+    fn transmit_msg(&mut self, _msg: ActionMsg, _stop: bool) {}
+}
@@ -1,7 +1,7 @@
 
 
 
-use elements::{*, action::*};
+use elements::{*, action::*, theme::*, easing::*, transition::*};
 
 
 
@@ -111,12 +111,25 @@ impl Element for Layers {
     }
     // TODO maybe implement?
     fn set_floating(&mut self, _floating: bool) {}
+    fn set_opacity(&mut self, opacity: f32) {
+        for el in &mut self.layers {
+            el.set_opacity(opacity);
+        }
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        for el in &mut self.layers {
+            el.set_theme(theme);
+        }
+    }
 
     fn stop(&mut self) {
         for el in &mut self.layers {
             el.stop();
         }
     }
+    fn is_animating(&self) -> bool {
+        self.layers.iter().any(|el| el.is_animating())
+    }
     fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
         use conrod::{widget, Widget, Colorable, Borderable};
         if let Some(ref ids) = self.ids {
@@ -222,6 +235,7 @@ pub struct List {
     elements: Vec<Box<Element>>,
     ring: Ring<i32>,
     alignment: ListAlignment,
+    transition: Option<LayoutTransition>,
 
     is_setup: bool,
     frame: Frame<i32>,
@@ -238,6 +252,7 @@ impl List {
             elements: Vec::new(),
             ring: Ring::new(),
             alignment,
+            transition: None,
             is_setup: false,
             frame: Frame::new(),
             global_center: Vec2::zero(),
@@ -246,6 +261,16 @@ impl List {
         })
     }
 
+    /// animate pushing, inserting and removing elements.
+    pub fn with_transition(mut self, duration_ms: f64, easing: Easing) -> Box<Self> {
+        self.transition = Some(LayoutTransition::new(duration_ms, easing));
+        Box::new(self)
+    }
+
+    pub fn set_transition(&mut self, transition: Option<LayoutTransition>) {
+        self.transition = transition;
+    }
+
     pub fn push(&mut self, element: Box<Element>) {
         let n = self.elements.len();
         self.insert(n, element);
@@ -253,66 +278,131 @@ impl List {
 
     pub fn insert(&mut self, index: usize, element: Box<Element>) {
 
-        if index >= self.elements.len() {
+        let index = if index >= self.elements.len() {
             self.elements.push(element);
+            self.elements.len() - 1
         } else {
             self.elements.insert(index, element);
+            index
+        };
+        if let Some(ref mut tr) = self.transition {
+            tr.element_inserted(index);
         }
         if DEBUG { println!("inserting into ring...");}
         self.ring.insert(index, RingElement::new());
         if DEBUG { println!("... inserting into ring done.");}
 
-        self.rescale_elements();
+        self.animate_elements(Some(index));
         self.is_setup = false;
     }
 
+    /// removes the last element right away, the others move with the transition.
+    /// Use `dismiss` to let it shrink out.
     pub fn pop(&mut self) -> Option<Box<Element>> {
         let el = self.elements.pop();
         let _ = self.ring.pop();
-        self.rescale_elements();
+        let n = self.elements.len();
+        if let Some(ref mut tr) = self.transition {
+            tr.element_removed(n);
+        }
+        self.animate_elements(None);
         el
     }
 
+    /// removes the element right away, the others move with the transition.
+    /// Use `dismiss` to let it shrink out.
     pub fn remove(&mut self, index: usize) -> Box<Element> {
         let el = self.elements.remove(index);
         let _ = self.ring.remove(index);
-        self.rescale_elements();
+        if let Some(ref mut tr) = self.transition {
+            tr.element_removed(index);
+        }
+        self.animate_elements(None);
         el
     }
 
-    fn rescale_elements(&mut self) {
-        if DEBUG { println!("rescaling...");}
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
 
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// removes the element, with a transition it shrinks out before it's dropped.
+    pub fn dismiss(&mut self, index: usize) {
+        let opacity = match self.transition {
+            Some(ref tr) => tr.opacity(index),
+            None => 1.0,
+        };
+        let el = self.remove(index);
+        if let Some(ref mut tr) = self.transition {
+            tr.dismiss(el, opacity);
+        }
+    }
+
+    fn layout(&mut self) -> Vec<Frame<i32>> {
         let n = self.elements.len();
+        let mut frames = vec![Frame::new(); n];
 
         match self.alignment {
             ListAlignment::Horizontal => {
                 self.ring.resize(self.frame.width());
                 for ix in 0..n {
-                    let el = &mut self.elements[ix];
                     let x0 = self.ring.get_sum(ix);
                     let x1 = self.ring.get_sum(ix+1);
-                    el.set_frame(Frame{
+                    frames[ix] = Frame{
                         p0: Vec2{x: x0 + self.frame.p0.x, y: self.frame.p0.y},
                         p1: Vec2{x: x1 + self.frame.p0.x, y: self.frame.p1.y}
-                    }, self.global_center);
+                    };
                 }
             },
             ListAlignment::Vertical => {
                 for ix in 0..n {
                     self.ring.resize(self.frame.height());
-                    let el = &mut self.elements[n-1-ix];
                     let y0 = self.ring.get_sum(ix);
                     let y1 = self.ring.get_sum(ix+1);
-                    el.set_frame(Frame{
+                    frames[n-1-ix] = Frame{
                         p0: Vec2{x: self.frame.p0.x, y: y0 + self.frame.p0.y},
                         p1: Vec2{x: self.frame.p1.x, y: y1 + self.frame.p0.y}
-                    }, self.global_center);
+                    };
+                }
+            },
+        }
+        frames
+    }
+
+    fn rescale_elements(&mut self) {
+        if DEBUG { println!("rescaling...");}
+
+        let frames = self.layout();
+        match self.transition {
+            Some(ref mut tr) if tr.is_running() => {
+                tr.retarget(frames);
+                tr.apply(&mut self.elements, self.global_center);
+            },
+            _ => {
+                for (el, frame) in self.elements.iter_mut().zip(frames) {
+                    el.set_frame(frame, self.global_center);
                 }
             },
         }
         if DEBUG { println!("... rescaling done.");}
     }
+
+    // like rescale_elements, but moves the elements with the transition if there is one
+    fn animate_elements(&mut self, inserted: Option<usize>) {
+        if self.transition.is_none() {
+            self.rescale_elements();
+            return
+        }
+        let from = LayoutTransition::current_frames(&self.elements, inserted);
+        let frames = self.layout();
+        if let Some(ref mut tr) = self.transition {
+            tr.start(from, frames);
+            tr.apply(&mut self.elements, self.global_center);
+        }
+    }
 }
 
 impl Element for List {
@@ -341,12 +431,29 @@ impl Element for List {
     }
     // TODO maybe implement?
     fn set_floating(&mut self, _floating: bool){}
+    fn set_opacity(&mut self, opacity: f32) {
+        for el in &mut self.elements {
+            el.set_opacity(opacity);
+        }
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        for el in &mut self.elements {
+            el.set_theme(theme);
+        }
+    }
 
     fn stop(&mut self) {
         for el in &mut self.elements {
             el.stop();
         }
     }
+    fn is_animating(&self) -> bool {
+        let transition = match self.transition {
+            Some(ref tr) => tr.is_running(),
+            None => false,
+        };
+        transition || self.elements.iter().any(|el| el.is_animating())
+    }
     fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
         use conrod::{widget, Widget, Colorable};
         if let Some(ref ids) = self.ids {
@@ -355,6 +462,9 @@ impl Element for List {
         for el in &self.elements {
             el.build_window(ui, ressources);
         }
+        if let Some(ref tr) = self.transition {
+            tr.build_window(ui, ressources);
+        }
     }
 
     fn get_frame(&self) -> Frame<i32> {
@@ -422,6 +532,11 @@ impl Element for List {
     }
 
     fn transmit_msg(&mut self, msg: ActionMsg, stop: bool) {
+        if let ActionMsgData::Update = msg.msg {
+            if let Some(ref mut tr) = self.transition {
+                tr.apply(&mut self.elements, self.global_center);
+            }
+        }
         if !stop {
             for el in &mut self.elements {
                 el.transmit_msg(msg.clone(), false);
@@ -496,6 +611,9 @@ pub struct Pad {
 
     original_pad_size: PadElementSize,
     original_alignment: PadAlignment,
+    // opacity set by the parent and the own fade, the content gets both
+    opacity: f32,
+    fade: f32,
 }
 
 
@@ -517,6 +635,8 @@ impl Pad {
 
             original_pad_size: size,
             original_alignment: alignment,
+            opacity: 1.0,
+            fade: 1.0,
         })
     }
 
@@ -725,7 +845,17 @@ impl Animateable for Pad {
         self.rescale(frame, center, false);
     }
 
+    /// fades the whole content
+    fn animate_alpha(&mut self, alpha: f32) {
+        self.fade = alpha;
+        self.element.set_opacity(self.opacity * alpha);
+    }
+
     fn reset(&mut self) {
+        if self.fade != 1.0 {
+            self.fade = 1.0;
+            self.element.set_opacity(self.opacity);
+        }
         self.alignment = self.original_alignment;
         self.pad_size = self.original_pad_size;
         let center = self.global_center;
@@ -757,10 +887,20 @@ impl Element for Pad {
     fn set_floating(&mut self, floating: bool) {
         self.element.set_floating(floating);
     }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+        self.element.set_opacity(opacity * self.fade);
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        self.element.set_theme(theme);
+    }
 
     fn stop(&mut self) {
         self.element.stop();
     }
+    fn is_animating(&self) -> bool {
+        self.element.is_animating()
+    }
     fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
         use conrod::{widget, Widget, Positionable};
         if let Some(ref ids) = self.ids {
@@ -0,0 +1,367 @@
+use conrod;
+
+use elements::{action::*, shared::*};
+use std::f64::consts::PI;
+
+
+
+
+
+
+
+
+/*
+d88888b  .d8b.  .d8888. d888888b d8b   db  d888b
+88'     d8' `8b 88'  YP   `88'   888o  88 88' Y8b
+88ooooo 88ooo88 `8bo.      88    88V8o 88 88
+88~~~~~ 88~~~88   `Y8b.    88    88 V8o88 88  ooo
+88.     88   88 db   8D   .88.   88  V888 88. ~8~
+Y88888P YP   YP `8888Y' Y888888P VP   V8P  Y888P
+
+
+*/
+
+
+
+
+const BACK: f64 = 1.70158;
+const BOUNCE_N: f64 = 7.5625;
+const BOUNCE_D: f64 = 2.75;
+
+
+
+
+/// Easing curves mapping the relative time `0..1` to the relative progress.
+/// Back and Elastic overshoot, i.e. the progress leaves `0..1` in between.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+    /// control points (x1, y1, x2, y2) as in CSS, the curve runs from (0,0) to (1,1).
+    CubicBezier(f64, f64, f64, f64),
+}
+
+impl Easing {
+    /// progress at the relative time `x`, which is clamped to `0..1`.
+    pub fn ease(&self, x: f64) -> f64 {
+        let x = if x < 0.0 { 0.0 } else if x > 1.0 { 1.0 } else { x };
+        match *self {
+            Easing::Linear => x,
+
+            Easing::QuadIn => power_in(x, 2),
+            Easing::QuadOut => power_out(x, 2),
+            Easing::QuadInOut => power_in_out(x, 2),
+            Easing::CubicIn => power_in(x, 3),
+            Easing::CubicOut => power_out(x, 3),
+            Easing::CubicInOut => power_in_out(x, 3),
+            Easing::QuintIn => power_in(x, 5),
+            Easing::QuintOut => power_out(x, 5),
+            Easing::QuintInOut => power_in_out(x, 5),
+
+            Easing::BackIn => back_in(x),
+            Easing::BackOut => 1.0 - back_in(1.0 - x),
+            Easing::BackInOut => {
+                let c = BACK * 1.525;
+                if x < 0.5 {
+                    let u = 2.0 * x;
+                    u * u * ((c + 1.0) * u - c) / 2.0
+                } else {
+                    let u = 2.0 * x - 2.0;
+                    (u * u * ((c + 1.0) * u + c) + 2.0) / 2.0
+                }
+            },
+
+            Easing::ElasticIn => elastic_in(x),
+            Easing::ElasticOut => 1.0 - elastic_in(1.0 - x),
+            Easing::ElasticInOut => {
+                if x == 0.0 || x == 1.0 { return x }
+                let c = 2.0 * PI / 4.5;
+                let s = ((20.0 * x - 11.125) * c).sin();
+                if x < 0.5 {
+                    -(2f64).powf(20.0 * x - 10.0) * s / 2.0
+                } else {
+                    (2f64).powf(-20.0 * x + 10.0) * s / 2.0 + 1.0
+                }
+            },
+
+            Easing::BounceIn => 1.0 - bounce_out(1.0 - x),
+            Easing::BounceOut => bounce_out(x),
+            Easing::BounceInOut => {
+                if x < 0.5 {
+                    (1.0 - bounce_out(1.0 - 2.0 * x)) / 2.0
+                } else {
+                    (1.0 + bounce_out(2.0 * x - 1.0)) / 2.0
+                }
+            },
+
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                let s = bezier_solve(x, x1, x2);
+                bezier(s, y1, y2)
+            },
+        }
+    }
+
+    /// progress at the time `t` of an animation of length `duration`.
+    pub fn at(&self, t: f64, duration: f64) -> f64 {
+        if duration <= 0.0 { return self.ease(1.0) }
+        self.ease(t / duration)
+    }
+}
+
+
+fn power_in(x: f64, p: i32) -> f64 {
+    x.powi(p)
+}
+
+fn power_out(x: f64, p: i32) -> f64 {
+    1.0 - (1.0 - x).powi(p)
+}
+
+fn power_in_out(x: f64, p: i32) -> f64 {
+    if x < 0.5 {
+        (2f64).powi(p - 1) * x.powi(p)
+    } else {
+        1.0 - (2.0 - 2.0 * x).powi(p) / 2.0
+    }
+}
+
+fn back_in(x: f64) -> f64 {
+    (BACK + 1.0) * x * x * x - BACK * x * x
+}
+
+fn elastic_in(x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 { return x }
+    let c = 2.0 * PI / 3.0;
+    -(2f64).powf(10.0 * x - 10.0) * ((10.0 * x - 10.75) * c).sin()
+}
+
+fn bounce_out(x: f64) -> f64 {
+    if x < 1.0 / BOUNCE_D {
+        BOUNCE_N * x * x
+    } else if x < 2.0 / BOUNCE_D {
+        let x = x - 1.5 / BOUNCE_D;
+        BOUNCE_N * x * x + 0.75
+    } else if x < 2.5 / BOUNCE_D {
+        let x = x - 2.25 / BOUNCE_D;
+        BOUNCE_N * x * x + 0.9375
+    } else {
+        let x = x - 2.625 / BOUNCE_D;
+        BOUNCE_N * x * x + 0.984375
+    }
+}
+
+// one coordinate of the cubic bezier from 0 to 1 with control values p1 and p2
+fn bezier(s: f64, p1: f64, p2: f64) -> f64 {
+    let r = 1.0 - s;
+    3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+}
+
+fn bezier_slope(s: f64, p1: f64, p2: f64) -> f64 {
+    let r = 1.0 - s;
+    3.0 * r * r * p1 + 6.0 * r * s * (p2 - p1) + 3.0 * s * s * (1.0 - p2)
+}
+
+// curve parameter s with bezier(s) == x, first Newton, then bisection as fallback
+fn bezier_solve(x: f64, x1: f64, x2: f64) -> f64 {
+    const EPS: f64 = 1e-7;
+
+    let mut s = x;
+    for _ in 0..8 {
+        let err = bezier(s, x1, x2) - x;
+        if err.abs() < EPS { return s }
+        let d = bezier_slope(s, x1, x2);
+        if d.abs() < EPS { break }
+        s -= err / d;
+    }
+
+    let (mut lo, mut hi) = (0.0, 1.0);
+    s = x;
+    for _ in 0..64 {
+        let v = bezier(s, x1, x2);
+        if (v - x).abs() < EPS { break }
+        if v < x { lo = s } else { hi = s }
+        s = (lo + hi) / 2.0;
+    }
+    s
+}
+
+
+
+
+
+
+
+
+
+
+/*
+d888888b db   d8b   db d88888b d88888b d8b   db
+`~~88~~' 88   I8I   88 88'     88'     888o  88
+   88    88   I8I   88 88ooooo 88ooooo 88V8o 88
+   88    Y8   I8I   88 88~~~~~ 88~~~~~ 88 V8o88
+   88    `8b d8'8b d8' 88.     88.     88  V888
+   YP     `8b8' `8d8'  Y88888P Y88888P VP   V8P
+
+
+*/
+
+
+
+
+/// interpolates between two Dims. Absolute and Relative can not be mixed
+/// without knowing the frame, so mixed pairs jump to `to` at the end.
+pub fn lerp_dim(from: Dim, to: Dim, s: f64) -> Dim {
+    match (from, to) {
+        (Dim::Absolute(a), Dim::Absolute(b)) =>
+            Dim::Absolute((a as f64 + (b - a) as f64 * s).round() as i32),
+        (Dim::Relative(a), Dim::Relative(b)) =>
+            Dim::Relative(a + (b - a) * s),
+        _ => if s < 1.0 { from } else { to },
+    }
+}
+
+
+
+/// Tweens from one `(Dim, Dim)` to another using an easing curve.
+/// Can be used as SizeAnimation as well as PositionAnimation.
+#[derive(Debug, Copy, Clone)]
+pub struct Tween {
+    from: (Dim, Dim),
+    to: (Dim, Dim),
+    easing: Easing,
+    there_and_back: bool,
+}
+
+impl Tween {
+    pub fn new(from: (Dim, Dim), to: (Dim, Dim), easing: Easing) -> Self {
+        Tween {
+            from,
+            to,
+            easing,
+            there_and_back: false,
+        }
+    }
+
+    /// runs to `to` in the first half of the duration and back to `from` in the second.
+    pub fn with_there_and_back(mut self, there_and_back: bool) -> Self {
+        self.there_and_back = there_and_back;
+        self
+    }
+
+    pub fn get_easing(&self) -> Easing {
+        self.easing
+    }
+
+    pub fn value(&self, t: f64, duration: f64) -> (Dim, Dim) {
+        let x = if duration <= 0.0 { 1.0 } else { t / duration };
+        let x = if self.there_and_back {
+            1.0 - (2.0 * x - 1.0).abs()
+        } else {
+            x
+        };
+        let s = self.easing.ease(x);
+        (
+            lerp_dim(self.from.0, self.to.0, s),
+            lerp_dim(self.from.1, self.to.1, s),
+        )
+    }
+}
+
+impl SizeAnimation for Tween {
+    fn calc(&self, t: f64, duration: f64) -> (Dim, Dim) {
+        self.value(t, duration)
+    }
+}
+
+impl PositionAnimation for Tween {
+    fn calc(&self, t: f64, duration: f64) -> (Dim, Dim) {
+        self.value(t, duration)
+    }
+}
+
+
+
+/// interpolates between two colors in RGBA.
+pub fn lerp_color(from: conrod::Color, to: conrod::Color, s: f64) -> conrod::Color {
+    let conrod::color::Rgba(r0,g0,b0,a0) = from.to_rgb();
+    let conrod::color::Rgba(r1,g1,b1,a1) = to.to_rgb();
+    let s = s as f32;
+    conrod::color::Color::Rgba(
+        r0 + (r1 - r0) * s,
+        g0 + (g1 - g0) * s,
+        b0 + (b1 - b0) * s,
+        a0 + (a1 - a0) * s,
+    )
+}
+
+
+
+/// Cross-fades from one color to another using an easing curve.
+#[derive(Debug, Copy, Clone)]
+pub struct ColorTween {
+    from: conrod::Color,
+    to: conrod::Color,
+    easing: Easing,
+}
+
+impl ColorTween {
+    pub fn new(from: conrod::Color, to: conrod::Color, easing: Easing) -> Self {
+        ColorTween { from, to, easing }
+    }
+}
+
+impl ColorAnimation for ColorTween {
+    fn calc(&self, t: f64, duration: f64) -> conrod::Color {
+        lerp_color(self.from, self.to, self.easing.at(t, duration))
+    }
+}
+
+
+
+/// Fades the opacity from one value to another using an easing curve.
+#[derive(Debug, Copy, Clone)]
+pub struct AlphaTween {
+    from: f32,
+    to: f32,
+    easing: Easing,
+}
+
+impl AlphaTween {
+    pub fn new(from: f32, to: f32, easing: Easing) -> Self {
+        AlphaTween { from, to, easing }
+    }
+
+    pub fn fade_in(easing: Easing) -> Self {
+        AlphaTween::new(0.0, 1.0, easing)
+    }
+
+    pub fn fade_out(easing: Easing) -> Self {
+        AlphaTween::new(1.0, 0.0, easing)
+    }
+}
+
+impl AlphaAnimation for AlphaTween {
+    fn calc(&self, t: f64, duration: f64) -> f32 {
+        let s = self.easing.at(t, duration) as f32;
+        let a = self.from + (self.to - self.from) * s;
+        // overshooting easings must not leave the valid range
+        if a < 0.0 { 0.0 } else if a > 1.0 { 1.0 } else { a }
+    }
+}
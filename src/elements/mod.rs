@@ -5,6 +5,17 @@ pub mod basic;
 pub mod action;
 pub mod shared;
 pub mod structures;
+pub mod plot;
+pub mod canvas;
+pub mod easing;
+pub mod timeline;
+pub mod transition;
+pub mod spring;
+pub mod sprite;
+pub mod animated_image;
+pub mod atlas;
+pub mod theme;
+pub mod style;
 
 
 use conrod;
@@ -12,21 +23,42 @@ use conrod::backend::glium::glium::{self, Surface};
 use conrod::position::Rect;
 use time;
 use std::sync::mpsc::{self, Sender, Receiver};
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::fs;
 
-use find_folder;
 use image;
+#[cfg(feature = "svg")]
+use nsvg;
+
+use error::Error;
 
 
 
 const DEBUG: bool = false;
 
+/// NotoSans-Regular, embedded so that a Window works without an assets folder.
+pub const DEFAULT_FONT: &'static [u8] = include_bytes!("../../assets/fonts/NotoSans/NotoSans-Regular.ttf");
+
+// frame interval while animations are running
+const ANIMATION_DT_NS: u64 = 1_000_000_000 / 60;
+// sleep per loop while nothing happens
+const IDLE_SLEEP_MS: u64 = 5;
+// interval for checking ressource files for changes
+const HOT_RELOAD_INTERVAL_NS: u64 = 500_000_000;
+// SVGs are rasterized again if the drawn size differs by more than this fraction
+#[cfg(feature = "svg")]
+const SVG_RESCALE_TOLERANCE: f32 = 0.2;
+
 
 
 use elements::shared::*;
 use elements::action::*;
 use elements::structures::*;
+use elements::theme::*;
+use elements::style::*;
 
 
 
@@ -72,8 +104,15 @@ pub trait Element {
 
     fn set_parent_widget(&mut self, parent: conrod::widget::id::Id);
     fn set_floating(&mut self, floating: bool);
+    /// multiplies the alpha of everything drawn by the element, forwarded by containers.
+    fn set_opacity(&mut self, _opacity: f32) {}
+    /// restyles everything that wasn't styled explicitly, forwarded by containers.
+    fn set_theme(&mut self, _theme: &Theme) {}
 
     fn stop(&mut self) {}
+    /// true while the element needs regular `Update`s. The Window only
+    /// schedules frames while at least one element is animating.
+    fn is_animating(&self) -> bool { false }
     fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources);
 
     fn get_frame(&self) -> Frame<i32>;
@@ -132,64 +171,760 @@ Y8   I8I   88    88    88 V8o88 88   88 88    88 Y8   I8I   88
 
 */
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum SourceKind {
+    Font,
+    Image,
+    Animation,
+    #[cfg(feature = "svg")]
+    Svg,
+}
+
+// file a ressource was loaded from, for hot-reloading
+#[derive(Debug, Clone)]
+struct Source {
+    path: PathBuf,
+    kind: SourceKind,
+    modified: Option<SystemTime>,
+}
+
+impl Source {
+    fn new(path: &Path, kind: SourceKind) -> Self {
+        Source {
+            path: path.to_path_buf(),
+            kind,
+            modified: Self::modified(path),
+        }
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+}
+
 pub struct WindowRessources {
     fonts: HashMap<String, conrod::text::font::Id>,
     image_map: conrod::image::Map<glium::texture::Texture2d>,
-    images: HashMap<String, (u32, u32, conrod::image::Id)>
+    images: HashMap<String, (u32, u32, conrod::image::Id)>,
+    // frame delays in ms, the frames are stored as images under frame_id()
+    animations: HashMap<String, Vec<f64>>,
+    // fonts and images have separate ids, so a font and an image may share one
+    font_sources: HashMap<String, Source>,
+    sources: HashMap<String, Source>,
+    // ids which were looked up without being added
+    unknown: RefCell<HashSet<String>>,
+
+    // texture memory limit in bytes, see set_texture_budget()
+    budget: Option<usize>,
+    // images dropped to stay within the budget, loaded again when they are drawn
+    evicted: HashSet<String>,
+    requested: RefCell<HashSet<String>>,
+    // counts the builds, the images remember in which one they were drawn last
+    frame: u64,
+    last_used: RefCell<HashMap<String, u64>>,
+    // position of images packed into an atlas, in texture coordinates from the bottom left
+    offsets: HashMap<String, (u32, u32)>,
+    // parsed SVGs with the scale they are rasterized at, and the sizes they were drawn in
+    #[cfg(feature = "svg")]
+    svgs: HashMap<String, (nsvg::SvgImage, f32)>,
+    #[cfg(feature = "svg")]
+    svg_sizes: RefCell<HashMap<String, (u32, u32)>>,
+    // images decoded on worker threads with the number of their request, only
+    // the result of the latest request for an id is used
+    loading: HashMap<String, u64>,
+    requests: u64,
+    decoded_sender: Sender<(String, u64, PathBuf, Result<image::RgbaImage, Error>)>,
+    decoded_receiver: Receiver<(String, u64, PathBuf, Result<image::RgbaImage, Error>)>,
 }
 impl WindowRessources {
     pub fn new() -> Self {
+        let (decoded_sender, decoded_receiver) = mpsc::channel();
         WindowRessources {
             fonts: HashMap::new(),
             image_map: conrod::image::Map::new(),
             images: HashMap::new(),
+            animations: HashMap::new(),
+            font_sources: HashMap::new(),
+            sources: HashMap::new(),
+            unknown: RefCell::new(HashSet::new()),
+            budget: None,
+            evicted: HashSet::new(),
+            requested: RefCell::new(HashSet::new()),
+            frame: 0,
+            last_used: RefCell::new(HashMap::new()),
+            offsets: HashMap::new(),
+            #[cfg(feature = "svg")]
+            svgs: HashMap::new(),
+            #[cfg(feature = "svg")]
+            svg_sizes: RefCell::new(HashMap::new()),
+            loading: HashMap::new(),
+            requests: 0,
+            decoded_sender,
+            decoded_receiver,
         }
     }
 
-    pub fn add_font(&mut self,  ui: &mut conrod::Ui, id: String, path: &Path) {
-        self.fonts.insert(
-            id,
-            ui.fonts.insert_from_file(path).unwrap()
-        );
-        println!("font loaded into windowressource.");
-        println!("self.fonts {:?}", self.fonts);
+    pub fn add_font(&mut self,  ui: &mut conrod::Ui, id: String, path: &Path) -> Result<FontHandle, Error> {
+        Self::check_file(path)?;
+        let font = ui.fonts.insert_from_file(path)
+            .map_err(|e| Error::Decode(path.to_path_buf(), format!("{:?}", e)))?;
+        self.font_sources.insert(id.clone(), Source::new(path, SourceKind::Font));
+        self.fonts.insert(id.clone(), font);
+        Ok(FontHandle::new(id))
+    }
+
+    pub fn add_font_from_bytes(&mut self, ui: &mut conrod::Ui, id: String, bytes: Vec<u8>) -> Result<FontHandle, Error> {
+        let font = conrod::text::FontCollection::from_bytes(bytes).into_font()
+            .ok_or_else(|| Error::InvalidData(format!("no font found for {}", id)))?;
+        let font = ui.fonts.insert(font);
+        self.font_sources.remove(&id);
+        self.fonts.insert(id.clone(), font);
+        Ok(FontHandle::new(id))
     }
 
+    /// the font with the given id, warns once if there is none.
     pub fn font(&self, id: &String) -> Option<&conrod::text::font::Id> {
-        self.fonts.get(id)
+        let font = self.fonts.get(id);
+        if font.is_none() {
+            self.warn_unknown("font", id);
+        }
+        font
     }
 
+    /// the image with the given id, warns once if there is none.
     pub fn image(&self, id: &String) -> Option<&(u32,u32,conrod::image::Id)> {
-        self.images.get(id)
+        let image = self.images.get(id);
+        if image.is_some() {
+            self.last_used.borrow_mut().insert(id.clone(), self.frame);
+        } else if self.evicted.contains(id) {
+            self.requested.borrow_mut().insert(id.clone());
+        } else if !self.loading.contains_key(id) {
+            self.warn_unknown("image", id);
+        }
+        image
     }
 
-    pub fn add_image(&mut self, display: &glium::Display, id: String, path: &Path) {
-        let img = Self::load_image(display, path);
-        let (w,h) = (img.get_width(), img.get_height().unwrap());
-        self.images.insert(
-            id,
-            (
-                w, h,
-                self.image_map.insert(
-                    Self::load_image(display, path)
-                )
-            )
-        );
-        println!("image loaded into windowressource.");
-        println!("self.images {:?}", self.images);
+    /// position of an image inside the texture it is stored in, (0,0) unless it
+    /// was packed into an atlas. In texture coordinates from the bottom left.
+    pub fn image_offset(&self, id: &String) -> (u32, u32) {
+        self.offsets.get(id).cloned().unwrap_or((0, 0))
+    }
+
+    pub fn has_font(&self, handle: &FontHandle) -> bool {
+        self.fonts.contains_key(handle.id())
+    }
+
+    pub fn has_image(&self, handle: &ImageHandle) -> bool {
+        self.images.contains_key(handle.id())
+    }
+
+    /// fails if the texture refers to an image which wasn't added. Dropped and
+    /// still loading images count as added.
+    pub fn check(&self, texture: &Texture) -> Result<(), Error> {
+        let id = texture.get_id();
+        if self.images.contains_key(&id) || self.evicted.contains(&id) || self.loading.contains_key(&id) {
+            Ok(())
+        } else {
+            Err(Error::UnknownId(id))
+        }
+    }
+
+    /// fails if the font refers to a font which wasn't added.
+    pub fn check_font(&self, font: &Font) -> Result<(), Error> {
+        let id = font.get_font_id();
+        if self.fonts.contains_key(&id) {
+            Ok(())
+        } else {
+            Err(Error::UnknownId(id))
+        }
+    }
+
+    // elements look up their ressources on every build, so only tell once
+    fn warn_unknown(&self, kind: &str, id: &String) {
+        if self.unknown.borrow_mut().insert(format!("{}/{}", kind, id)) {
+            println!("warning: there is no {} with the id \"{}\", it won't be drawn", kind, id);
+        }
+    }
+
+    /// loads an image, an image with the same id is replaced.
+    pub fn add_image(&mut self, display: &glium::Display, id: String, path: &Path) -> Result<ImageHandle, Error> {
+        let img = Self::load_image(display, path)?;
+        self.store_image(id.clone(), img);
+        self.sources.insert(id.clone(), Source::new(path, SourceKind::Image));
+        self.enforce_budget(Some(&id));
+        Ok(ImageHandle::new(id))
+    }
+
+    /// decodes the image on a worker thread and returns immediately. The image is
+    /// uploaded by `upload_decoded` once it's ready, until then textures show their
+    /// placeholder.
+    pub fn add_image_async(&mut self, id: String, path: &Path) -> ImageHandle {
+        use std::thread;
+
+        self.requests += 1;
+        let request = self.requests;
+        self.loading.insert(id.clone(), request);
+        let sender = self.decoded_sender.clone();
+        let path = path.to_path_buf();
+        let handle = ImageHandle::new(id.clone());
+        thread::spawn(move || {
+            let result = Self::check_file(&path).and_then(|_| {
+                image::open(&path)
+                    .map(|img| img.to_rgba())
+                    .map_err(|e| Self::image_error(&path, e))
+            });
+            let _ = sender.send((id, request, path, result));
+        });
+        handle
+    }
+
+    /// uploads the images decoded since the last call. Returns ImageLoaded(id)
+    /// or ImageFailed(id, error) for each of them.
+    pub fn upload_decoded(&mut self, display: &glium::Display) -> Vec<ActionMsgData> {
+        let mut msgs = Vec::new();
+        while let Ok((id, request, path, result)) = self.decoded_receiver.try_recv() {
+            // removed or requested again in the meantime
+            if self.loading.get(&id) != Some(&request) { continue }
+            self.loading.remove(&id);
+
+            match result.and_then(|rgba_image| Self::upload(display, rgba_image)) {
+                Ok(texture) => {
+                    self.store_image(id.clone(), texture);
+                    self.sources.insert(id.clone(), Source::new(&path, SourceKind::Image));
+                    self.enforce_budget(Some(&id));
+                    msgs.push(ActionMsgData::ImageLoaded(id));
+                },
+                Err(e) => {
+                    println!("loading {} failed: {}", id, e);
+                    msgs.push(ActionMsgData::ImageFailed(id, format!("{}", e)));
+                },
+            }
+        }
+        msgs
+    }
+
+    /// same as add_image, for clarity if the id exists already. Replacing an
+    /// atlas removes the images packed into it.
+    pub fn replace_image(&mut self, display: &glium::Display, id: String, path: &Path) -> Result<ImageHandle, Error> {
+        self.add_image(display, id, path)
+    }
+
+    /// frees the texture(s) of an image or animated image. Returns whether it existed.
+    pub fn remove_image(&mut self, id: &str) -> bool {
+        let mut removed = self.evicted.remove(id) | self.loading.remove(id).is_some();
+        if self.offsets.remove(id).is_some() {
+            // the atlas stays for the other images
+            removed |= self.images.remove(id).is_some();
+        } else if let Some((_, _, image_id)) = self.images.remove(id) {
+            self.image_map.remove(image_id);
+            removed = true;
+            self.remove_packed(image_id);
+        }
+        // the first frame shares the texture with the image itself
+        if let Some(delays) = self.animations.remove(id) {
+            for i in 0..delays.len() {
+                if let Some((_, _, image_id)) = self.images.remove(&Self::frame_id(id, i)) {
+                    self.image_map.remove(image_id);
+                }
+            }
+        }
+        self.sources.remove(id);
+        self.last_used.borrow_mut().remove(id);
+        #[cfg(feature = "svg")]
+        {
+            self.svgs.remove(id);
+        }
+        removed
+    }
+
+    // removing or replacing an atlas removes the images packed into it
+    fn remove_packed(&mut self, atlas_id: conrod::image::Id) {
+        let packed: Vec<String> = self.offsets.keys()
+            .filter(|packed| self.images.get(*packed).map_or(false, |image| image.2 == atlas_id))
+            .cloned()
+            .collect();
+        for packed in packed {
+            self.offsets.remove(&packed);
+            self.images.remove(&packed);
+        }
+    }
+
+    // puts the texture under `id`, replacing the texture of an existing image in place
+    fn store_image(&mut self, id: String, texture: glium::texture::Texture2d) -> conrod::image::Id {
+        if self.animations.contains_key(&id) {
+            self.remove_image(&id);
+        }
+        let (w,h) = (texture.get_width(), texture.get_height().unwrap_or(1));
+        // an image packed into an atlas gets its own texture again
+        let packed = self.offsets.remove(&id).is_some();
+        let image_id = match self.images.get(&id) {
+            Some(&(_, _, image_id)) if !packed => {
+                self.remove_packed(image_id);
+                self.image_map.replace(image_id, texture);
+                image_id
+            },
+            _ => self.image_map.insert(texture),
+        };
+        self.evicted.remove(&id);
+        // a pending background load would overwrite it
+        self.loading.remove(&id);
+        self.images.insert(id, (w, h, image_id));
+        image_id
+    }
+
+    /// packs the given images into one texture stored as `id`. The images keep their
+    /// ids and are drawn from their part of the atlas, their own textures are freed.
+    /// The textures are read back from the graphics card, prefer `add_atlas_from_paths`
+    /// for images which aren't loaded yet.
+    pub fn add_atlas(&mut self, display: &glium::Display, id: String, images: &[ImageHandle]) -> Result<ImageHandle, Error> {
+        let mut packed = Vec::new();
+        for handle in images {
+            let image_id = handle.id().to_string();
+            match self.images.get(&image_id) {
+                Some(&(w, h, texture_id))
+                    if !self.offsets.contains_key(&image_id) && !self.animations.contains_key(&image_id) => {
+                    // rows from the bottom, like they were uploaded
+                    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = match self.image_map.get(&texture_id) {
+                        Some(texture) => texture.read(),
+                        None => continue,
+                    };
+                    let mut rgba_image = image::RgbaImage::new(w, h);
+                    for (row, line) in pixels.iter().enumerate().take(h as usize) {
+                        for (col, &(r, g, b, a)) in line.iter().enumerate().take(w as usize) {
+                            rgba_image.put_pixel(col as u32, h - 1 - row as u32, image::Rgba([r, g, b, a]));
+                        }
+                    }
+                    packed.push((image_id, rgba_image));
+                },
+                _ => println!("warning: {} can't be packed into the atlas {}", image_id, id),
+            }
+        }
+        self.store_atlas(display, id, packed)
+    }
+
+    /// loads the images (id, path) and packs them into one texture stored as `id`
+    /// without uploading them one by one. Returns the handles of the atlas and the images.
+    pub fn add_atlas_from_paths(
+        &mut self,
+        display: &glium::Display,
+        id: String,
+        images: &[(String, PathBuf)]
+    ) -> Result<(ImageHandle, Vec<ImageHandle>), Error> {
+        let mut packed = Vec::new();
+        for &(ref image_id, ref path) in images {
+            Self::check_file(path)?;
+            let rgba_image = image::open(path)
+                .map_err(|e| Self::image_error(path, e))?
+                .to_rgba();
+            packed.push((image_id.clone(), rgba_image));
+        }
+        let atlas = self.store_atlas(display, id, packed)?;
+        let mut handles = Vec::new();
+        for &(ref image_id, ref path) in images {
+            self.sources.insert(image_id.clone(), Source::new(path, SourceKind::Image));
+            handles.push(ImageHandle::new(image_id.clone()));
+        }
+        Ok((atlas, handles))
+    }
+
+    fn store_atlas(&mut self, display: &glium::Display, id: String, packed: Vec<(String, image::RgbaImage)>) -> Result<ImageHandle, Error> {
+        let sizes: Vec<(u32, u32)> = packed.iter().map(|&(_, ref img)| img.dimensions()).collect();
+        let ((atlas_w, atlas_h), positions) = atlas::pack(&sizes);
+
+        // rows from the bottom, like textures are uploaded
+        let mut data = vec![0u8; atlas_w as usize * atlas_h as usize * 4];
+        for (&(_, ref img), &position) in packed.iter().zip(positions.iter()) {
+            let (w, h) = img.dimensions();
+            atlas::blit(&mut data, atlas_w, position, (w, h), |x, y| img.get_pixel(x, h - 1 - y).data);
+        }
+
+        let raw_image = glium::texture::RawImage2d::from_raw_rgba(data, (atlas_w, atlas_h));
+        let texture = glium::texture::Texture2d::new(display, raw_image)
+            .map_err(|e| Error::Upload(format!("{:?}", e)))?;
+        let atlas_id = self.store_image(id.clone(), texture);
+
+        for ((image_id, img), position) in packed.into_iter().zip(positions.into_iter()) {
+            let (w, h) = img.dimensions();
+            // images packed before share the texture of their atlas
+            let own_texture = !self.offsets.contains_key(&image_id);
+            if let Some((_, _, texture_id)) = self.images.insert(image_id.clone(), (w, h, atlas_id)) {
+                if own_texture {
+                    self.image_map.remove(texture_id);
+                }
+            }
+            self.evicted.remove(&image_id);
+            self.offsets.insert(image_id, position);
+        }
+        println!("{} images packed into the atlas {} ({}x{}).", sizes.len(), id, atlas_w, atlas_h);
+        Ok(ImageHandle::new(id))
+    }
+
+    /// loads an SVG, which is rasterized again whenever it is drawn in a
+    /// noticeably different size or DPI. Texture cuts on SVGs are in pixels of the
+    /// current raster and therefore not useful.
+    #[cfg(feature = "svg")]
+    pub fn add_svg(&mut self, display: &glium::Display, id: String, path: &Path) -> Result<ImageHandle, Error> {
+        Self::check_file(path)?;
+        let svg = nsvg::parse_file(path, nsvg::Units::Pixel, 96.0)
+            .map_err(|e| Error::Decode(path.to_path_buf(), format!("{:?}", e)))?;
+        self.remove_image(&id);
+        self.svgs.insert(id.clone(), (svg, 1.0));
+        self.rasterize(display, &id, 1.0)?;
+        self.sources.insert(id.clone(), Source::new(path, SourceKind::Svg));
+        Ok(ImageHandle::new(id))
+    }
+
+    #[cfg(feature = "svg")]
+    fn rasterize(&mut self, display: &glium::Display, id: &String, scale: f32) -> Result<(), Error> {
+        let (w, h, data) = match self.svgs.get(id) {
+            Some(&(ref svg, _)) => svg.rasterize_to_raw_rgba(scale)
+                .map_err(|e| Error::InvalidData(format!("{:?}", e)))?,
+            None => return Ok(()),
+        };
+        let rgba_image = image::RgbaImage::from_raw(w, h, data)
+            .ok_or_else(|| Error::InvalidData(format!("rasterized {} has the wrong size", id)))?;
+        let texture = Self::upload(display, rgba_image)?;
+        self.store_image(id.clone(), texture);
+        if let Some(svg) = self.svgs.get_mut(id) {
+            svg.1 = scale;
+        }
+        Ok(())
+    }
+
+    /// tells in which size (in logical pixels) an image is drawn, SVGs are rasterized to fit.
+    pub fn request_size(&self, id: &String, w: u32, h: u32) {
+        #[cfg(feature = "svg")]
+        {
+            if self.svgs.contains_key(id) {
+                let mut sizes = self.svg_sizes.borrow_mut();
+                let size = sizes.entry(id.clone()).or_insert((0, 0));
+                *size = (size.0.max(w), size.1.max(h));
+            }
+        }
+        #[cfg(not(feature = "svg"))]
+        {
+            let _ = (id, w, h);
+        }
+    }
+
+    /// rasterizes the SVGs drawn in another size since the last call. Returns whether any were.
+    pub fn rasterize_requested(&mut self, display: &glium::Display, dpi: f64) -> bool {
+        #[cfg(feature = "svg")]
+        {
+            let sizes: Vec<(String, (u32, u32))> = self.svg_sizes.borrow_mut().drain().collect();
+            let mut changed = false;
+            for (id, (w, h)) in sizes {
+                let scale = match self.svgs.get(&id) {
+                    Some(&(ref svg, current)) => {
+                        let scale = (w as f32 / svg.width()).max(h as f32 / svg.height()) * dpi as f32;
+                        if scale <= 0.0 || (scale - current).abs() <= current * SVG_RESCALE_TOLERANCE {
+                            continue
+                        }
+                        scale
+                    },
+                    None => continue,
+                };
+                match self.rasterize(display, &id, scale) {
+                    Ok(()) => changed = true,
+                    Err(e) => println!("rasterizing {} failed: {}", id, e),
+                }
+            }
+            changed
+        }
+        #[cfg(not(feature = "svg"))]
+        {
+            let _ = (display, dpi);
+            false
+        }
+    }
+
+    /// bytes of texture memory used by the loaded images, 4 bytes per pixel.
+    pub fn texture_memory(&self) -> usize {
+        let mut counted = HashSet::new();
+        self.images.iter()
+            .filter(|&(id, image)| !self.offsets.contains_key(id) && counted.insert(image.2))
+            .map(|(_, &(w, h, _))| w as usize * h as usize * 4)
+            .sum()
+    }
+
+    /// number of textures in GPU memory.
+    pub fn texture_count(&self) -> usize {
+        self.images.values().map(|image| image.2).collect::<HashSet<_>>().len()
+    }
+
+    /// limits the texture memory in bytes. If it's exceeded, the least recently drawn
+    /// images loaded from files are dropped and loaded again when they are drawn.
+    /// Images from memory and animated images are never dropped.
+    pub fn set_texture_budget(&mut self, budget: Option<usize>) {
+        self.budget = budget;
+        self.enforce_budget(None);
+    }
+
+    fn enforce_budget(&mut self, keep: Option<&str>) {
+        let budget = match self.budget {
+            Some(budget) => budget,
+            None => return,
+        };
+        let mut memory = self.texture_memory();
+        if memory <= budget { return }
+
+        let mut candidates: Vec<(u64, String)> = {
+            let last_used = self.last_used.borrow();
+            self.images.keys()
+                .filter(|id| Some(id.as_str()) != keep && self.is_evictable(id))
+                .map(|id| (last_used.get(id).cloned().unwrap_or(0), id.clone()))
+                // images on screen stay, even if the budget is too small for them
+                .filter(|&(used, _)| used < self.frame || self.frame == 0)
+                .collect()
+        };
+        candidates.sort();
+
+        for (_, id) in candidates {
+            if memory <= budget { break }
+            if let Some((w, h, image_id)) = self.images.remove(&id) {
+                self.image_map.remove(image_id);
+                memory -= w as usize * h as usize * 4;
+                self.evicted.insert(id);
+            }
+        }
+    }
+
+    fn is_evictable(&self, id: &String) -> bool {
+        !self.animations.contains_key(id)
+            && !self.offsets.contains_key(id)
+            && self.sources.get(id).map_or(false, |source| source.kind == SourceKind::Image)
+    }
+
+    /// loads dropped images which were needed since the last call. Returns whether any were.
+    pub fn load_requested(&mut self, display: &glium::Display) -> bool {
+        let requested: Vec<String> = self.requested.borrow_mut().drain().collect();
+        let mut loaded = false;
+        for id in requested {
+            let path = match self.sources.get(&id) {
+                Some(source) if source.kind == SourceKind::Image => source.path.clone(),
+                _ => continue,
+            };
+            match self.add_image(display, id.clone(), &path) {
+                Ok(_) => loaded = true,
+                Err(e) => println!("loading {} again failed: {}", id, e),
+            }
+        }
+        loaded
+    }
+
+    /// called before every build to track which images are in use.
+    pub fn next_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// decodes an image file (png, jpg, ...) from memory.
+    pub fn add_image_from_bytes(&mut self, display: &glium::Display, id: String, bytes: &[u8]) -> Result<ImageHandle, Error> {
+        let rgba_image = image::load_from_memory(bytes)
+            .map_err(|e| Error::InvalidData(format!("{}", e)))?
+            .to_rgba();
+        self.insert_image(display, id, rgba_image)
+    }
+
+    /// `rgba` holds 4 bytes per pixel, row by row from the top left.
+    pub fn add_image_from_rgba(
+        &mut self,
+        display: &glium::Display,
+        id: String,
+        width: u32, height: u32,
+        rgba: Vec<u8>
+    ) -> Result<ImageHandle, Error> {
+        let len = rgba.len();
+        let rgba_image = image::RgbaImage::from_raw(width, height, rgba)
+            .ok_or_else(|| Error::InvalidData(
+                format!("{} bytes are too few for {}x{} rgba pixels", len, width, height)
+            ))?;
+        self.insert_image(display, id, rgba_image)
+    }
+
+    fn insert_image(&mut self, display: &glium::Display, id: String, rgba_image: image::RgbaImage) -> Result<ImageHandle, Error> {
+        let texture = Self::upload(display, rgba_image)?;
+        self.store_image(id.clone(), texture);
+        self.sources.remove(&id);
+        self.enforce_budget(Some(&id));
+        Ok(ImageHandle::new(id))
+    }
+
+    /// reloads fonts and images whose files changed since they were loaded. The
+    /// string ids stay the same. Returns whether anything was reloaded.
+    ///
+    /// conrod can't replace or remove fonts, so the old version of a reloaded
+    /// font stays in memory until the window is closed.
+    pub fn reload_changed(&mut self, display: &glium::Display, ui: &mut conrod::Ui) -> bool {
+        let mut changed = Vec::new();
+        for (id, source) in self.font_sources.iter_mut().chain(self.sources.iter_mut()) {
+            let modified = Source::modified(&source.path);
+            if modified.is_some() && modified != source.modified {
+                source.modified = modified;
+                changed.push((id.clone(), source.clone()));
+            }
+        }
+
+        let mut reloaded = false;
+        for (id, source) in changed {
+            let result = match source.kind {
+                SourceKind::Font => self.add_font(ui, id.clone(), &source.path).map(|_| ()),
+                SourceKind::Image => self.reload_image(display, &id, &source.path),
+                SourceKind::Animation => self.add_animated_image(display, id.clone(), &source.path).map(|_| ()),
+                #[cfg(feature = "svg")]
+                SourceKind::Svg => self.add_svg(display, id.clone(), &source.path).map(|_| ()),
+            };
+            match result {
+                Ok(()) => {
+                    println!("reloaded {} from {}", id, source.path.display());
+                    reloaded = true;
+                },
+                // e.g. the file is still being written, try again on the next change
+                Err(e) => println!("reloading {} failed: {}", id, e),
+            }
+        }
+        reloaded
+    }
+
+    // replaces the texture but keeps the conrod image id, dropped images are loaded when needed
+    fn reload_image(&mut self, display: &glium::Display, id: &String, path: &Path) -> Result<(), Error> {
+        if self.images.contains_key(id) {
+            let img = Self::load_image(display, path)?;
+            self.store_image(id.clone(), img);
+        }
+        Ok(())
+    }
+
+    /// loads all frames of an animated GIF. The first frame is available under `id`
+    /// as a still image as well. APNG is not supported, APNGs and other formats
+    /// are loaded as a single frame.
+    pub fn add_animated_image(&mut self, display: &glium::Display, id: String, path: &Path) -> Result<ImageHandle, Error> {
+        let frames = Self::load_frames(path)?;
+        self.remove_image(&id);
+        let mut delays = Vec::new();
+        for (i, (rgba_image, delay)) in frames.into_iter().enumerate() {
+            let (w,h) = rgba_image.dimensions();
+            let texture = Self::upload(display, rgba_image)?;
+            let image_id = self.store_image(Self::frame_id(&id, i), texture);
+            if i == 0 {
+                self.images.insert(id.clone(), (w, h, image_id));
+            }
+            delays.push(delay);
+        }
+        self.animations.insert(id.clone(), delays);
+        self.sources.insert(id.clone(), Source::new(path, SourceKind::Animation));
+        Ok(ImageHandle::new(id))
+    }
+
+    /// frame delays in ms of an animated image.
+    pub fn animation(&self, id: &String) -> Option<&Vec<f64>> {
+        self.animations.get(id)
+    }
+
+    /// image id of the frame `index` of an animated image.
+    pub fn frame_id(id: &str, index: usize) -> String {
+        format!("{}#{}", id, index)
+    }
+
+    // reading errors are kept apart from broken files
+    fn image_error(path: &Path, e: image::ImageError) -> Error {
+        match e {
+            image::ImageError::IoError(e) => Error::io(path, e),
+            e => Error::Decode(path.to_path_buf(), format!("{}", e)),
+        }
+    }
+
+    fn check_file(path: &Path) -> Result<(), Error> {
+        if path.exists() {
+            Ok(())
+        } else {
+            Err(Error::MissingFile(path.to_path_buf()))
+        }
+    }
+
+    // decodes the frames with their delays in ms
+    fn load_frames(path: &Path) -> Result<Vec<(image::RgbaImage, f64)>, Error> {
+        use gif::SetParameter;
+        use std::fs::File;
+
+        Self::check_file(path)?;
+        let decode_error = |e: image::ImageError| Self::image_error(path, e);
+
+        let is_gif = path.extension()
+            .map_or(false, |ext| ext.to_string_lossy().to_lowercase() == "gif");
+        if !is_gif {
+            let rgba_image = image::open(path).map_err(decode_error)?.to_rgba();
+            return Ok(vec![(rgba_image, 0.0)]);
+        }
+
+        // the gif decoder of image only gives the first frame
+        let gif_error = |e: gif::DecodingError| Error::Decode(path.to_path_buf(), format!("{}", e));
+        let file = File::open(path).map_err(|e| Error::io(path, e))?;
+        let mut decoder = gif::Decoder::new(file);
+        decoder.set(gif::ColorOutput::RGBA);
+        let mut reader = decoder.read_info().map_err(gif_error)?;
+        let (w, h) = (reader.width() as u32, reader.height() as u32);
+        let mut canvas = image::RgbaImage::new(w, h);
+        let mut frames = Vec::new();
+        while let Some(frame) = reader.read_next_frame().map_err(gif_error)? {
+            let (left, top) = (frame.left as u32, frame.top as u32);
+            let (fw, fh) = (frame.width as u32, frame.height as u32);
+            let previous = match frame.dispose {
+                gif::DisposalMethod::Previous => Some(canvas.clone()),
+                _ => None,
+            };
+            // frames may only cover a part of the canvas and are painted over the previous ones
+            for (i, pixel) in frame.buffer.chunks(4).enumerate() {
+                let (x, y) = (left + i as u32 % fw.max(1), top + i as u32 / fw.max(1));
+                if x < w && y < h && pixel.len() == 4 && pixel[3] > 0 {
+                    canvas.put_pixel(x, y, image::Rgba([pixel[0], pixel[1], pixel[2], pixel[3]]));
+                }
+            }
+            // delays are given in 10ms, like browsers treat missing ones as 100ms
+            let delay = frame.delay as f64 * 10.0;
+            frames.push((canvas.clone(), if delay < 10.0 { 100.0 } else { delay }));
+
+            // prepare the canvas for the next frame
+            match frame.dispose {
+                gif::DisposalMethod::Background => {
+                    // browsers clear to transparent instead of the background color
+                    for y in top..(top + fh).min(h) {
+                        for x in left..(left + fw).min(w) {
+                            canvas.put_pixel(x, y, image::Rgba([0, 0, 0, 0]));
+                        }
+                    }
+                },
+                gif::DisposalMethod::Previous => {
+                    if let Some(previous) = previous {
+                        canvas = previous;
+                    }
+                },
+                _ => (),
+            }
+        }
+        if frames.is_empty() {
+            return Err(Error::Decode(path.to_path_buf(), "no frames".to_string()));
+        }
+        Ok(frames)
     }
 
     // ? from conrod-example "image_button.rs"
     // Load an image from our assets folder as a texture we can draw to the screen.
-    fn load_image<P>(display: &glium::Display, path: P) -> glium::texture::Texture2d
+    fn load_image<P>(display: &glium::Display, path: P) -> Result<glium::texture::Texture2d, Error>
         where P: AsRef<Path>,
     {
         let path = path.as_ref();
-        let rgba_image = image::open(&Path::new(&path)).unwrap().to_rgba();
+        Self::check_file(path)?;
+        let rgba_image = image::open(path)
+            .map_err(|e| Self::image_error(path, e))?
+            .to_rgba();
+        Self::upload(display, rgba_image)
+    }
+
+    fn upload(display: &glium::Display, rgba_image: image::RgbaImage) -> Result<glium::texture::Texture2d, Error> {
         let image_dimensions = rgba_image.dimensions();
         let raw_image = glium::texture::RawImage2d::from_raw_rgba_reversed(&rgba_image.into_raw(), image_dimensions);
-        let texture = glium::texture::Texture2d::new(display, raw_image).unwrap();
-        texture
+        glium::texture::Texture2d::new(display, raw_image)
+            .map_err(|e| Error::Upload(format!("{:?}", e)))
     }
 }
 
@@ -216,6 +951,12 @@ pub struct Window {
 
     ids: Option<WindowIds>,
     ressources: WindowRessources,
+    hot_reload: bool,
+    default_font: FontHandle,
+    // set by set_theme, the shown theme is changed by the stylesheet
+    theme: Theme,
+    stylesheet: Option<Stylesheet>,
+    current_theme: Theme,
 }
 
 impl Window {
@@ -226,6 +967,7 @@ impl Window {
         if let Some(ref mut el) = self.element {
             el.set_parent_widget(ids.window);
             el.setup(&mut self.ui);
+            el.set_theme(&self.current_theme);
             println!("setup(): element setup.");
         }
         self.ids = Some(ids);
@@ -234,24 +976,186 @@ impl Window {
 
 
 
-    pub fn add_font(&mut self, id: String, path: &Path) {
+    pub fn add_font(&mut self, id: String, path: &Path) -> Result<FontHandle, Error> {
         self.ressources.add_font(
             &mut self.ui,
             id, 
             path
-        );
+        )
     }
 
-    pub fn add_image(&mut self, id: String, path: &Path) {
+    pub fn add_image(&mut self, id: String, path: &Path) -> Result<ImageHandle, Error> {
         self.ressources.add_image(
             &mut self.display, 
             id, 
             path
-        );
+        )
+    }
+
+    pub fn replace_image(&mut self, id: String, path: &Path) -> Result<ImageHandle, Error> {
+        self.ressources.replace_image(
+            &mut self.display,
+            id,
+            path
+        )
+    }
+
+    pub fn remove_image(&mut self, id: &str) -> bool {
+        self.ressources.remove_image(id)
+    }
+
+    /// loads the image in the background. The window sends ImageLoaded(id) or
+    /// ImageFailed(id, error) when it's done.
+    pub fn add_image_async(&mut self, id: String, path: &Path) -> ImageHandle {
+        self.ressources.add_image_async(id, path)
+    }
+
+    /// see WindowRessources::add_svg
+    #[cfg(feature = "svg")]
+    pub fn add_svg(&mut self, id: String, path: &Path) -> Result<ImageHandle, Error> {
+        self.ressources.add_svg(
+            &mut self.display,
+            id,
+            path
+        )
+    }
+
+    /// see WindowRessources::add_atlas_from_paths
+    pub fn add_atlas_from_paths(&mut self, id: String, images: &[(String, PathBuf)]) -> Result<(ImageHandle, Vec<ImageHandle>), Error> {
+        self.ressources.add_atlas_from_paths(
+            &mut self.display,
+            id,
+            images
+        )
+    }
+
+    /// see WindowRessources::add_atlas
+    pub fn add_atlas(&mut self, id: String, images: &[ImageHandle]) -> Result<ImageHandle, Error> {
+        self.ressources.add_atlas(
+            &mut self.display,
+            id,
+            images
+        )
+    }
+
+    /// see WindowRessources::set_texture_budget
+    pub fn set_texture_budget(&mut self, budget: Option<usize>) {
+        self.ressources.set_texture_budget(budget);
+    }
+
+    pub fn texture_memory(&self) -> usize {
+        self.ressources.texture_memory()
+    }
+
+    /// see WindowRessources::check
+    pub fn check(&self, texture: &Texture) -> Result<(), Error> {
+        self.ressources.check(texture)
+    }
+
+    /// see WindowRessources::check_font
+    pub fn check_font(&self, font: &Font) -> Result<(), Error> {
+        self.ressources.check_font(font)
+    }
+
+    pub fn add_font_from_bytes(&mut self, id: String, bytes: Vec<u8>) -> Result<FontHandle, Error> {
+        self.ressources.add_font_from_bytes(
+            &mut self.ui,
+            id,
+            bytes
+        )
+    }
+
+    pub fn add_image_from_bytes(&mut self, id: String, bytes: &[u8]) -> Result<ImageHandle, Error> {
+        self.ressources.add_image_from_bytes(
+            &mut self.display,
+            id,
+            bytes
+        )
+    }
+
+    pub fn add_image_from_rgba(&mut self, id: String, width: u32, height: u32, rgba: Vec<u8>) -> Result<ImageHandle, Error> {
+        self.ressources.add_image_from_rgba(
+            &mut self.display,
+            id,
+            width, height,
+            rgba
+        )
+    }
+
+    pub fn add_animated_image(&mut self, id: String, path: &Path) -> Result<ImageHandle, Error> {
+        self.ressources.add_animated_image(
+            &mut self.display,
+            id,
+            path
+        )
+    }
+
+    /// the embedded NotoSans-Regular.
+    pub fn default_font(&self) -> FontHandle {
+        self.default_font.clone()
+    }
+
+    /// reloads fonts, images and the stylesheet added from files whenever the
+    /// files change, e.g. while designing textures. Off by default.
+    pub fn set_hot_reload(&mut self, hot_reload: bool) {
+        self.hot_reload = hot_reload;
+    }
+
+    /// default look of elements without own graphics or fonts. To switch it
+    /// while running send `ActionMsgData::Theme` to the window.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.apply_theme();
     }
 
+    /// the shown theme, including the stylesheet.
+    pub fn get_theme(&self) -> &Theme {
+        &self.current_theme
+    }
 
+    /// styles elements by type, id and class, see `Stylesheet`. Reloaded on
+    /// changes with hot reload.
+    pub fn load_stylesheet(&mut self, path: &Path) -> Result<(), Error> {
+        self.stylesheet = Some(Stylesheet::load(path)?);
+        self.apply_theme();
+        Ok(())
+    }
+
+    pub fn set_stylesheet(&mut self, stylesheet: Option<Stylesheet>) {
+        self.stylesheet = stylesheet;
+        self.apply_theme();
+    }
+
+    fn apply_theme(&mut self) {
+        let theme = match self.stylesheet {
+            Some(ref sheet) => sheet.apply(&self.theme),
+            None => self.theme.clone(),
+        };
+        if let Some(ref mut el) = self.element {
+            el.set_theme(&theme);
+        }
+        self.current_theme = theme;
+    }
 
+    fn reload_stylesheet(&mut self) -> bool {
+        let reloaded = match self.stylesheet {
+            Some(ref mut sheet) if sheet.is_modified() => match sheet.reload() {
+                Ok(()) => {
+                    println!("reloaded the stylesheet");
+                    true
+                },
+                Err(e) => {
+                    println!("reloading the stylesheet failed: {}", e);
+                    false
+                },
+            },
+            _ => false,
+        };
+        if reloaded {
+            self.apply_theme();
+        }
+        reloaded
+    }
 
     pub fn add_element(&mut self, element: Box<Element>) {
         self.element = Some(element);
@@ -281,7 +1185,7 @@ impl Window {
     }
 
 
-    pub fn new(title: String, width: u32, height: u32) -> Self {
+    pub fn new(title: String, width: u32, height: u32) -> Result<Self, Error> {
         // build window
         let events_loop = glium::glutin::EventsLoop::new();
         let window = glium::glutin::WindowBuilder::new()
@@ -292,7 +1196,7 @@ impl Window {
             .with_multisampling(8);
         let display = glium::Display::new(
             window, context, &events_loop
-        ).unwrap();
+        ).map_err(|e| Error::Window(format!("{:?}", e)))?;
 
 
 
@@ -302,14 +1206,12 @@ impl Window {
         ).build();
 
 
-        let assets = find_folder::Search::KidsThenParents(3, 5).for_folder("assets").unwrap();
-
         let mut ressources = WindowRessources::new();
-        ressources.add_font(
+        let default_font = ressources.add_font_from_bytes(
             &mut ui,
             "NotoSans-Regular".to_string(), 
-            &assets.join("fonts/NotoSans/NotoSans-Regular.ttf")
-        );
+            DEFAULT_FONT.to_vec()
+        )?;
 
         // Add a `Font` to the `Ui`'s `font::Map` from file.
         //let assets = find_folder::Search::KidsThenParents(3, 5).for_folder("assets").unwrap();
@@ -323,7 +1225,8 @@ impl Window {
         ui.fonts.insert_from_file(FONT_PATH).unwrap();*/
 
         // connect conrod::render::Primitives to glium Surface
-        let renderer = conrod::backend::glium::Renderer::new(&display).unwrap();
+        let renderer = conrod::backend::glium::Renderer::new(&display)
+            .map_err(|e| Error::Window(format!("{:?}", e)))?;
 
         // image mapping, here: none
         //let image_map = conrod::image::Map::<glium::texture::Texture2d>::new();
@@ -332,7 +1235,7 @@ impl Window {
             = mpsc::channel();
 
 
-        Window {
+        Ok(Window {
             events_loop,
             display,
             renderer,
@@ -343,8 +1246,13 @@ impl Window {
             senders: Vec::new(),
             selfsender,
             ids: None,
-            ressources
-        }
+            ressources,
+            hot_reload: false,
+            default_font,
+            theme: Theme::default(),
+            stylesheet: None,
+            current_theme: Theme::default(),
+        })
     }
 
     pub fn run(&mut self) {
@@ -362,6 +1270,8 @@ impl Window {
         // events
         let mut events = Vec::new();
         let mut t0 = time::precise_time_ns();
+        let mut t_animation = t0;
+        let mut t_reload = t0;
 
         let mut window_frame = Frame::new();
 
@@ -577,7 +1487,67 @@ impl Window {
                 }
             }
 
+            // request frames only while something is animating
+            let animating = match self.element {
+                Some(ref el) => el.is_animating(),
+                None => false,
+            };
+            if animating {
+                let now = time::precise_time_ns();
+                if now - t_animation >= ANIMATION_DT_NS {
+                    t_animation = now;
+                    if let Some(ref mut el) = self.element {
+                        el.transmit_msg(ActionMsg{
+                            sender_id: "Window".to_string(),
+                            msg: ActionMsgData::Update
+                        }, false);
+                    }
+                    self.ui.needs_redraw();
+                    update = true;
+                }
+            }
+
+            if self.hot_reload {
+                let now = time::precise_time_ns();
+                if now - t_reload >= HOT_RELOAD_INTERVAL_NS {
+                    t_reload = now;
+                    if self.ressources.reload_changed(&self.display, &mut self.ui) {
+                        self.ui.needs_redraw();
+                        update = true;
+                    }
+                    if self.reload_stylesheet() {
+                        self.ui.needs_redraw();
+                        update = true;
+                        resized = true;
+                    }
+                }
+            }
+
+            // images dropped for the texture budget which are needed again
+            if self.ressources.load_requested(&self.display) {
+                self.ui.needs_redraw();
+                update = true;
+            }
+
+            // images loaded in the background
+            let decoded = self.ressources.upload_decoded(&self.display);
+            if !decoded.is_empty() {
+                for msg in decoded {
+                    self.send(msg);
+                }
+                self.ui.needs_redraw();
+                update = true;
+            }
+
+            // SVGs drawn in another size
+            let dpi = self.display.gl_window().hidpi_factor() as f64;
+            if self.ressources.rasterize_requested(&self.display, dpi) {
+                self.ui.needs_redraw();
+                update = true;
+            }
+
             // check if msgs have to be processed and transmit through chain
+            let mut theme = None;
             for receiver in &self.receivers {
                 'receive: loop {
                     match receiver.try_recv() {
@@ -593,6 +1563,7 @@ impl Window {
 
                             match msg.msg {
                                 ActionMsgData::Update => self.ui.needs_redraw(),
+                                ActionMsgData::Theme(t) => theme = Some(t),
                                 _ => ()
                             }
                         },
@@ -601,9 +1572,18 @@ impl Window {
                 }
             }
 
+            // restyle and lay out again, e.g. for another scroll bar width
+            if let Some(theme) = theme {
+                self.set_theme(theme);
+                self.ui.needs_redraw();
+                resized = true;
+            }
+
             if let Some(ref mut el) = self.element {
                 if !el.is_setup() {
                     el.setup(&mut self.ui);
+                    // new elements follow the theme as well
+                    el.set_theme(&self.current_theme);
                     update = true;
                 }
             }
@@ -615,6 +1595,7 @@ impl Window {
             }
 
             if update {
+                self.ressources.next_frame();
                 let ui = &mut self.ui.set_widgets();
                 let res = &self.ressources;
                 if DEBUG { println!("run() start building...");}
@@ -639,13 +1620,21 @@ impl Window {
                 if DEBUG { println!("run() preparing target for drawing...");}
                 self.renderer.fill(&self.display, primitives, &self.ressources.image_map);
                 let mut target = self.display.draw();
-                target.clear_color(0.0, 0.0, 0.0, 1.0);
+                let bg = self.current_theme.background.to_fsa();
+                target.clear_color(bg[0], bg[1], bg[2], bg[3]);
 
                 if DEBUG { println!("run() drawing..."); }
                 self.renderer.draw(&self.display, &mut target, &self.ressources.image_map).unwrap();
                 target.finish().unwrap();
                 if DEBUG { println!("run() loop finished."); }
             }
+
+            // idle
+            if !update && !animating && fps <= 0.0 {
+                use std::thread;
+                use std::time::Duration;
+                thread::sleep(Duration::from_millis(IDLE_SLEEP_MS));
+            }
         }
 
         if let Some(ref mut el) = self.element {
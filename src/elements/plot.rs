@@ -0,0 +1,1641 @@
+use conrod;
+use time;
+use image;
+
+use elements::{*, action::*, container::*, basic::fade, theme::*};
+use std::sync::mpsc::Sender;
+use std::path::{Path, PathBuf};
+use std::fs::File;
+use std::io::{self, Write, BufWriter};
+use std::f64;
+use std::i32;
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+
+
+
+
+const DEBUG: bool = false;
+
+// maximum number of ticks per axis that can be drawn
+const MAX_TICKS: usize = 32;
+// minimal distance between two ticks in pixel
+const TICK_SPACING: i32 = 80;
+
+const MARGIN_LEFT: i32 = 70;
+const MARGIN_BOTTOM: i32 = 40;
+const MARGIN_RIGHT: i32 = 20;
+const MARGIN_TOP: i32 = 20;
+
+// maximal distance in pixel of the mouse to a data point to show the crosshair
+const HOVER_DISTANCE: f64 = 30.0;
+// largest width and height of exported images, they are rendered on the UI thread
+const MAX_EXPORT_SIZE: u32 = 8192;
+// smallest SymLog threshold, smaller or invalid ones are clamped to it
+const MIN_SYMLOG_THRESHOLD: f64 = 1e-12;
+
+
+
+
+
+
+/*
+ .d8b.  db    db d888888b .d8888.
+d8' `8b `8b  d8'   `88'   88'  YP
+88ooo88  `8bd8'     88    `8bo.
+88~~~88  .dPYb.     88      `Y8b.
+88   88 .8P  Y8.   .88.   db   8D
+YP   YP YP    YP Y888888P `8888Y'
+
+
+*/
+
+
+
+
+#[derive(Debug, Clone)]
+pub enum AxisScale {
+    Linear,
+    Log10,
+    /// logarithmic for large values, linear in `[-threshold, threshold]`.
+    SymLog(f64),
+    /// values are seconds since unix epoch (UTC).
+    Time,
+    /// values are indices into the list of categories.
+    Categorical(Vec<String>),
+}
+
+
+#[derive(Debug, Clone)]
+pub struct Axis {
+    scale: AxisScale,
+    range: Option<(f64,f64)>,
+    label: String,
+}
+
+impl Axis {
+    /// a SymLog threshold which isn't positive and finite is clamped.
+    pub fn new(scale: AxisScale) -> Self {
+        Axis {
+            scale: Self::clamp_scale(scale),
+            range: None,
+            label: "".to_string(),
+        }
+    }
+
+    fn clamp_scale(scale: AxisScale) -> AxisScale {
+        match scale {
+            AxisScale::SymLog(c) if c == f64::INFINITY => AxisScale::SymLog(f64::MAX),
+            AxisScale::SymLog(c) if !(c >= MIN_SYMLOG_THRESHOLD) => {
+                if DEBUG { println!("Axis --- SymLog threshold {} clamped", c); }
+                AxisScale::SymLog(MIN_SYMLOG_THRESHOLD)
+            },
+            scale => scale,
+        }
+    }
+
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub fn with_label(mut self, label: String) -> Self {
+        self.label = label;
+        self
+    }
+
+    /// a SymLog threshold which isn't positive and finite is clamped.
+    pub fn set_scale(&mut self, scale: AxisScale) {
+        self.scale = Self::clamp_scale(scale);
+    }
+
+    pub fn set_range(&mut self, range: Option<(f64,f64)>) {
+        self.range = range;
+    }
+
+    pub fn set_label(&mut self, label: String) {
+        self.label = label;
+    }
+
+    pub fn get_scale(&self) -> &AxisScale {
+        &self.scale
+    }
+
+    pub fn get_range(&self) -> Option<(f64,f64)> {
+        self.range
+    }
+
+    pub fn get_label(&self) -> String {
+        self.label.clone()
+    }
+
+    /// checks if the value can be shown on this axis at all.
+    pub fn is_valid(&self, v: f64) -> bool {
+        match self.scale {
+            AxisScale::Log10 => v.is_finite() && v > 0.0,
+            _ => v.is_finite(),
+        }
+    }
+
+    /// maps a data value into the (linear) space of the axis.
+    pub fn transform(&self, v: f64) -> f64 {
+        match self.scale {
+            AxisScale::Log10 => v.log10(),
+            AxisScale::SymLog(c) => v.signum() * (1.0 + v.abs()/c).log10(),
+            _ => v,
+        }
+    }
+
+    /// maps a value from the space of the axis back to data.
+    pub fn inverse(&self, s: f64) -> f64 {
+        match self.scale {
+            AxisScale::Log10 => 10f64.powf(s),
+            AxisScale::SymLog(c) => s.signum() * c * (10f64.powf(s.abs()) - 1.0),
+            _ => s,
+        }
+    }
+
+    /// range of the axis in transformed space, either the fixed one or
+    /// the one covering all given data values.
+    pub fn get_span<I>(&self, values: I) -> (f64,f64) where I: Iterator<Item=f64> {
+        if let Some((min, max)) = self.range {
+            if self.is_valid(min) && self.is_valid(max) {
+                return (self.transform(min), self.transform(max));
+            }
+            // e.g. 0 on a log axis, the invalid end is taken from the data
+            let (d0, d1) = self.data_span(values);
+            let t0 = if self.is_valid(min) { self.transform(min) } else { d0 };
+            let t1 = if self.is_valid(max) { self.transform(max) } else { d1 };
+            return if t0 < t1 { (t0, t1) } else { (t1 - 1.0, t1) };
+        }
+        self.data_span(values)
+    }
+
+    // span of the valid data values in transformed space
+    fn data_span<I>(&self, values: I) -> (f64,f64) where I: Iterator<Item=f64> {
+        if let AxisScale::Categorical(ref names) = self.scale {
+            return (-0.5, names.len() as f64 - 0.5);
+        }
+
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        for v in values {
+            if !self.is_valid(v) { continue; }
+            let t = self.transform(v);
+            if t < min { min = t; }
+            if t > max { max = t; }
+        }
+
+        if !min.is_finite() || !max.is_finite() {
+            return (0.0, 1.0);
+        }
+        if max - min < 1e-12 {
+            return (min - 0.5, max + 0.5);
+        }
+        (min, max)
+    }
+
+    /// ticks as (position in transformed space, label) for an axis
+    /// of `length` pixel. The number of ticks adapts to the length,
+    /// so that labels stay readable when the frame is resized.
+    pub fn ticks(&self, min: f64, max: f64, length: i32) -> Vec<(f64, String)> {
+        let (min, max) = if min <= max {(min, max)} else {(max, min)};
+        let n = {
+            let n = (length / TICK_SPACING) as usize;
+            if n < 2 { 2 } else if n > MAX_TICKS { MAX_TICKS } else { n }
+        };
+
+        let mut res = match self.scale {
+            AxisScale::Linear => Self::linear_ticks(min, max, n),
+            AxisScale::Log10 => {
+                let mut res = Vec::new();
+                let k0 = min.ceil() as i32;
+                let k1 = max.floor() as i32;
+                let every = ((k1 - k0 + 1) as f64 / n as f64).ceil() as i32;
+                let every = if every < 1 { 1 } else { every };
+                for k in k0..(k1+1) {
+                    if k % every != 0 { continue; }
+                    res.push((k as f64, Self::format_decade(k)));
+                }
+                // less than one decade visible
+                if res.len() < 2 {
+                    res = Self::linear_ticks(10f64.powf(min), 10f64.powf(max), n)
+                        .into_iter()
+                        .filter(|&(v,_)| v > 0.0)
+                        .map(|(v,s)| (v.log10(), s))
+                        .collect();
+                }
+                res
+            },
+            AxisScale::SymLog(c) => {
+                let mut res = Vec::new();
+                let vmax = {
+                    let a = self.inverse(min).abs();
+                    let b = self.inverse(max).abs();
+                    if a > b { a } else { b }
+                };
+                if min <= 0.0 && max >= 0.0 {
+                    res.push((0.0, "0".to_string()));
+                }
+                let mut v = c;
+                while v.is_finite() && v <= vmax * 1.000001 {
+                    for &sign in &[-1.0, 1.0] {
+                        let t = self.transform(sign * v);
+                        if t >= min && t <= max {
+                            res.push((t, Self::format_number(sign * v, v)));
+                        }
+                    }
+                    v *= 10.0;
+                }
+                res.sort_by(|a,b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+                res
+            },
+            AxisScale::Time => Self::time_ticks(min, max, n),
+            AxisScale::Categorical(ref names) => {
+                let mut res = Vec::new();
+                for (i, name) in names.iter().enumerate() {
+                    let t = i as f64;
+                    if t >= min && t <= max {
+                        res.push((t, name.clone()));
+                    }
+                }
+                res
+            },
+        };
+
+        Self::thin_out(&mut res, min, max, length);
+        if DEBUG { println!("Axis ticks {:?}", res); }
+        res
+    }
+
+    // remove ticks which are closer than TICK_SPACING to their predecessor
+    fn thin_out(ticks: &mut Vec<(f64, String)>, min: f64, max: f64, length: i32) {
+        if length <= 0 || max <= min {
+            ticks.clear();
+            return;
+        }
+        let min_dist = (max - min) * TICK_SPACING as f64 / length as f64;
+        let mut last = f64::NEG_INFINITY;
+        ticks.retain(|&(t,_)| {
+            if t - last >= min_dist * 0.999 {
+                last = t;
+                true
+            } else {
+                false
+            }
+        });
+        ticks.truncate(MAX_TICKS);
+    }
+
+    fn linear_ticks(min: f64, max: f64, n: usize) -> Vec<(f64, String)> {
+        let mut res = Vec::new();
+        let raw = (max - min) / n as f64;
+        if !(raw > 0.0) { return res; }
+
+        let mag = 10f64.powf(raw.log10().floor());
+        let norm = raw / mag;
+        let step = mag * if norm <= 1.0 {
+            1.0
+        } else if norm <= 2.0 {
+            2.0
+        } else if norm <= 5.0 {
+            5.0
+        } else {
+            10.0
+        };
+
+        let mut k = (min / step).ceil();
+        while k * step <= max + step * 1e-9 {
+            let v = k * step;
+            res.push((v, Self::format_number(v, step)));
+            k += 1.0;
+        }
+        res
+    }
+
+    fn time_ticks(min: f64, max: f64, n: usize) -> Vec<(f64, String)> {
+        const STEPS: [f64; 20] = [
+            1.0, 2.0, 5.0, 10.0, 15.0, 30.0,
+            60.0, 120.0, 300.0, 600.0, 900.0, 1800.0,
+            3600.0, 7200.0, 10800.0, 21600.0, 43200.0,
+            86400.0, 172800.0, 604800.0
+        ];
+
+        let mut step = STEPS[STEPS.len()-1];
+        for s in STEPS.iter() {
+            if (max - min) / s <= n as f64 {
+                step = *s;
+                break;
+            }
+        }
+        while (max - min) / step > n as f64 {
+            step *= 2.0;
+        }
+
+        let format = if step < 60.0 {
+            "%H:%M:%S"
+        } else if step < 86400.0 {
+            "%H:%M"
+        } else {
+            "%Y-%m-%d"
+        };
+
+        let mut res = Vec::new();
+        let mut k = (min / step).ceil();
+        while k * step <= max {
+            let v = k * step;
+            let tm = time::at_utc(time::Timespec::new(v as i64, 0));
+            let label = match tm.strftime(format) {
+                Ok(s) => s.to_string(),
+                Err(_) => format!("{}", v),
+            };
+            res.push((v, label));
+            k += 1.0;
+        }
+        res
+    }
+
+    fn format_decade(k: i32) -> String {
+        if k >= -3 && k <= 4 {
+            Self::format_number(10f64.powi(k), 10f64.powi(k))
+        } else {
+            format!("1e{}", k)
+        }
+    }
+
+    fn format_number(v: f64, step: f64) -> String {
+        let step = step.abs();
+        if step >= 1e5 || (step < 1e-3 && step > 0.0) {
+            return format!("{:e}", v);
+        }
+        let decimals = if step >= 1.0 { 0 } else { (-step.log10().floor()) as usize };
+        // avoid "-0"
+        let v = if v.abs() < step * 1e-9 { 0.0 } else { v };
+        format!("{:.*}", decimals, v)
+    }
+}
+
+
+
+
+
+
+
+
+
+
+/*
+.d8888. d88888b d8888b. d888888b d88888b .d8888.
+88'  YP 88'     88  `8D   `88'   88'     88'  YP
+`8bo.   88ooooo 88oobY'    88    88ooooo `8bo.
+  `Y8b. 88~~~~~ 88`8b      88    88~~~~~   `Y8b.
+db   8D 88.     88 `88.   .88.   88.     db   8D
+`8888Y' Y88888P 88   YD Y888888P Y88888P `8888Y'
+
+
+*/
+
+
+
+#[derive(Debug, Clone)]
+pub struct Series {
+    name: String,
+    points: Vec<(f64,f64)>,
+    color: conrod::Color,
+    thickness: f64,
+}
+
+impl Series {
+    pub fn new(name: String, color: conrod::Color) -> Self {
+        Series {
+            name,
+            points: Vec::new(),
+            color,
+            thickness: 2.0,
+        }
+    }
+
+    pub fn with_points(mut self, points: Vec<(f64,f64)>) -> Self {
+        self.points = points;
+        self
+    }
+
+    pub fn with_thickness(mut self, thickness: f64) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn push(&mut self, x: f64, y: f64) {
+        self.points.push((x,y));
+    }
+
+    pub fn set_points(&mut self, points: Vec<(f64,f64)>) {
+        self.points = points;
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_points(&self) -> &Vec<(f64,f64)> {
+        &self.points
+    }
+
+    pub fn get_color(&self) -> conrod::Color {
+        self.color
+    }
+}
+
+
+
+
+
+
+
+
+
+
+/*
+ .d8b.  d8b   db d8b   db  .d88b.  d888888b  .d8b.  d888888b d888888b  .d88b.  d8b   db
+d8' `8b 888o  88 888o  88 .8P  Y8. `~~88~~' d8' `8b `~~88~~'   `88'   .8P  Y8. 888o  88
+88ooo88 88V8o 88 88V8o 88 88    88    88    88ooo88    88       88    88    88 88V8o 88
+88~~~88 88 V8o88 88 V8o88 88    88    88    88~~~88    88       88    88    88 88 V8o88
+88   88 88  V888 88  V888 `8b  d8'    88    88   88    88      .88.   `8b  d8' 88  V888
+YP   YP VP   V8P VP   V8P  `Y88P'     YP    YP   YP    YP    Y888888P  `Y88P'  VP   V8P
+
+
+*/
+
+
+
+/// everything is given in data coordinates.
+#[derive(Debug, Clone)]
+pub enum Annotation {
+    Text(f64, f64, String),
+    /// text at the first point with an arrow pointing to the second one.
+    Arrow(f64, f64, f64, f64, String),
+    HorizontalLine(f64, conrod::Color),
+    VerticalLine(f64, conrod::Color),
+}
+
+
+
+
+
+
+
+
+
+
+/*
+d8888b. db       .d88b.  d888888b
+88  `8D 88      .8P  Y8. `~~88~~'
+88oodD' 88      88    88    88
+88~~~   88      88    88    88
+88      88booo. `8b  d8'    88
+88      Y88888P  `Y88P'     YP
+
+
+*/
+
+
+
+widget_ids!(
+    #[derive(Clone)]
+    struct PlotIds {
+        plot,
+        area,
+        x_axis,
+        y_axis,
+        x_label,
+        y_label,
+        series[],
+        x_ticks[],
+        x_tick_labels[],
+        y_ticks[],
+        y_tick_labels[],
+        annotation_lines[],
+        annotation_heads[],
+        annotation_texts[],
+        legend,
+        legend_lines[],
+        legend_texts[],
+        crosshair_x,
+        crosshair_y,
+        crosshair_point,
+        tooltip,
+        tooltip_text,
+    }
+);
+
+
+pub struct Plot {
+    ids: Option<PlotIds>,
+    parent: Option<conrod::widget::id::Id>,
+    floating: bool,
+
+    id: String,
+    senders: Vec<Sender<ActionMsg>>,
+
+    series: Vec<Series>,
+    x_axis: Axis,
+    y_axis: Axis,
+    font: Font,
+    background: conrod::Color,
+
+    annotations: Vec<Annotation>,
+    legend: Option<PadAlignment>,
+    crosshair: bool,
+    // (series index, point index)
+    hover: Option<(usize, usize)>,
+
+    // spans of the current data, reset whenever series or axes change
+    spans: Cell<Option<((f64,f64),(f64,f64))>>,
+
+    // file path without extension and resolution for the export on right click
+    export: Option<(PathBuf, u32, u32)>,
+    export_font: Option<conrod::text::Font>,
+    opacity: f32,
+
+    is_setup: bool,
+    frame: Frame<i32>,
+    global_center: Vec2<i32>,
+    min_size: Vec2<i32>,
+    max_size: Vec2<i32>,
+}
+
+impl Plot {
+    pub fn new() -> Box<Self> {
+        Box::new(Plot {
+            ids: None,
+            parent: None,
+            floating: false,
+
+            id: "Plot".to_string(),
+            senders: Vec::new(),
+
+            series: Vec::new(),
+            x_axis: Axis::new(AxisScale::Linear),
+            y_axis: Axis::new(AxisScale::Linear),
+            font: Font::new("NotoSans-Regular".to_string(), 14, conrod::color::BLACK),
+            background: conrod::color::WHITE,
+
+            annotations: Vec::new(),
+            legend: None,
+            crosshair: false,
+            hover: None,
+
+            spans: Cell::new(None),
+
+            export: None,
+            export_font: None,
+            opacity: 1.0,
+
+            is_setup: false,
+            frame: Frame::new(),
+            global_center: Vec2::zero(),
+            min_size: Vec2{
+                x: MARGIN_LEFT + MARGIN_RIGHT + 2*TICK_SPACING,
+                y: MARGIN_BOTTOM + MARGIN_TOP + 2*TICK_SPACING
+            },
+            max_size: Vec2 {x: i32::MAX, y: i32::MAX},
+        })
+    }
+
+    pub fn with_series(mut self, series: Series) -> Box<Self> {
+        self.series.push(series);
+        self.spans.set(None);
+        Box::new(self)
+    }
+
+    pub fn with_x_axis(mut self, axis: Axis) -> Box<Self> {
+        self.x_axis = axis;
+        self.spans.set(None);
+        Box::new(self)
+    }
+
+    pub fn with_y_axis(mut self, axis: Axis) -> Box<Self> {
+        self.y_axis = axis;
+        self.spans.set(None);
+        Box::new(self)
+    }
+
+    pub fn with_background(mut self, color: conrod::Color) -> Box<Self> {
+        self.background = color;
+        Box::new(self)
+    }
+
+    pub fn with_annotation(mut self, annotation: Annotation) -> Box<Self> {
+        self.annotations.push(annotation);
+        Box::new(self)
+    }
+
+    pub fn with_legend(mut self, alignment: PadAlignment) -> Box<Self> {
+        self.legend = Some(alignment);
+        Box::new(self)
+    }
+
+    pub fn with_crosshair(mut self, crosshair: bool) -> Box<Self> {
+        self.crosshair = crosshair;
+        Box::new(self)
+    }
+
+    /// a right click on the plot exports it to `path` with extensions
+    /// ".csv" and ".png", the latter with the given resolution. Sends
+    /// Text(file) for every written file and ExportFailed(file, error) otherwise.
+    pub fn with_export(mut self, path: PathBuf, width: u32, height: u32) -> Box<Self> {
+        self.export = Some((path, width, height));
+        Box::new(self)
+    }
+
+    /// font for the labels in exported images. Without it, the image
+    /// contains no text.
+    pub fn with_export_font(mut self, font: conrod::text::Font) -> Box<Self> {
+        self.export_font = Some(font);
+        Box::new(self)
+    }
+
+    pub fn set_export_font(&mut self, font: Option<conrod::text::Font>) {
+        self.export_font = font;
+    }
+
+    pub fn push_series(&mut self, series: Series) {
+        self.series.push(series);
+        self.hover = None;
+        self.spans.set(None);
+    }
+
+    pub fn push_annotation(&mut self, annotation: Annotation) {
+        self.annotations.push(annotation);
+    }
+
+    pub fn clear_annotations(&mut self) {
+        self.annotations.clear();
+    }
+
+    pub fn set_legend(&mut self, alignment: Option<PadAlignment>) {
+        self.legend = alignment;
+    }
+
+    pub fn set_crosshair(&mut self, crosshair: bool) {
+        self.crosshair = crosshair;
+        self.hover = None;
+    }
+
+    /// currently hovered data point as (series name, x, y).
+    pub fn get_hover(&self) -> Option<(String, f64, f64)> {
+        match self.hover {
+            Some((s, p)) => {
+                let (x,y) = self.series[s].points[p];
+                Some((self.series[s].name.clone(), x, y))
+            },
+            None => None,
+        }
+    }
+
+    pub fn remove_series(&mut self, name: &str) -> Option<Series> {
+        self.hover = None;
+        let index = self.series.iter().position(|s| s.name == name);
+        match index {
+            Some(ix) => {
+                self.spans.set(None);
+                Some(self.series.remove(ix))
+            },
+            None => None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.series.clear();
+        self.hover = None;
+        self.spans.set(None);
+    }
+
+    pub fn series(&self) -> &Vec<Series> {
+        &self.series
+    }
+
+    pub fn series_mut(&mut self, name: &str) -> Option<&mut Series> {
+        self.hover = None;
+        self.spans.set(None);
+        self.series.iter_mut().find(|s| s.name == name)
+    }
+
+    pub fn set_x_axis(&mut self, axis: Axis) {
+        self.x_axis = axis;
+        self.spans.set(None);
+    }
+
+    pub fn set_y_axis(&mut self, axis: Axis) {
+        self.y_axis = axis;
+        self.spans.set(None);
+    }
+
+    pub fn x_axis_mut(&mut self) -> &mut Axis {
+        self.spans.set(None);
+        &mut self.x_axis
+    }
+
+    pub fn y_axis_mut(&mut self) -> &mut Axis {
+        self.spans.set(None);
+        &mut self.y_axis
+    }
+
+    /// part of the frame where data is drawn, without tick labels.
+    pub fn get_area(&self) -> Frame<i32> {
+        Self::area_of(self.frame)
+    }
+
+    fn area_of(frame: Frame<i32>) -> Frame<i32> {
+        Frame {
+            p0: Vec2{
+                x: frame.p0.x + MARGIN_LEFT,
+                y: frame.p0.y + MARGIN_BOTTOM
+            },
+            p1: Vec2{
+                x: frame.p1.x - MARGIN_RIGHT,
+                y: frame.p1.y - MARGIN_TOP
+            },
+        }
+    }
+
+    /// x and y range in transformed axis space, computed once per data change.
+    pub fn get_spans(&self) -> ((f64,f64), (f64,f64)) {
+        if let Some(spans) = self.spans.get() {
+            return spans;
+        }
+        let xs = self.series.iter().flat_map(|s| s.points.iter().map(|p| p.0));
+        let ys = self.series.iter().flat_map(|s| s.points.iter().map(|p| p.1));
+        let spans = (self.x_axis.get_span(xs), self.y_axis.get_span(ys));
+        self.spans.set(Some(spans));
+        spans
+    }
+
+    /// maps a data point to absolute pixel coordinates, `None` if the
+    /// point cannot be shown on the axes.
+    pub fn to_pixel(&self, x: f64, y: f64) -> Option<Vec2<f64>> {
+        self.project(self.get_area(), self.get_spans(), x, y)
+    }
+
+    // same as to_pixel, but with precalculated area and spans
+    fn project(
+        &self, area: Frame<i32>, spans: ((f64,f64),(f64,f64)), x: f64, y: f64
+    ) -> Option<Vec2<f64>> {
+        if !self.x_axis.is_valid(x) || !self.y_axis.is_valid(y) {
+            return None;
+        }
+        let ((x0,x1),(y0,y1)) = spans;
+        let tx = (self.x_axis.transform(x) - x0) / (x1 - x0);
+        let ty = (self.y_axis.transform(y) - y0) / (y1 - y0);
+        Some(Vec2{
+            x: area.p0.x as f64 + tx * area.width() as f64,
+            y: area.p0.y as f64 + ty * area.height() as f64,
+        })
+    }
+
+    /// maps absolute pixel coordinates back to data.
+    pub fn to_data(&self, x: f64, y: f64) -> (f64,f64) {
+        let ((x0,x1),(y0,y1)) = self.get_spans();
+        let area = self.get_area();
+        let tx = (x - area.p0.x as f64) / area.width() as f64;
+        let ty = (y - area.p0.y as f64) / area.height() as f64;
+        (
+            self.x_axis.inverse(x0 + tx * (x1 - x0)),
+            self.y_axis.inverse(y0 + ty * (y1 - y0)),
+        )
+    }
+
+    fn find_nearest(&self, x: f64, y: f64) -> Option<(usize, usize)> {
+        let mut res = None;
+        let mut dist = HOVER_DISTANCE * HOVER_DISTANCE;
+        let area = self.get_area();
+        let spans = self.get_spans();
+        for (s, series) in self.series.iter().enumerate() {
+            for (n, &(px,py)) in series.points.iter().enumerate() {
+                if let Some(p) = self.project(area, spans, px, py) {
+                    let d = (p.x - x).powi(2) + (p.y - y).powi(2);
+                    if d < dist {
+                        dist = d;
+                        res = Some((s, n));
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    fn send(&self, msg: ActionMsgData) {
+        for sender in &self.senders {
+            let _ = sender.send(ActionMsg{
+                sender_id: self.id.clone(),
+                msg: msg.clone()
+            });
+        }
+    }
+
+    fn legend_frame(&self) -> Frame<i32> {
+        self.legend_frame_in(self.get_area())
+    }
+
+    fn legend_frame_in(&self, area: Frame<i32>) -> Frame<i32> {
+        let size = self.font.get_size() as i32;
+        let chars = self.series.iter().map(|s| s.name.chars().count()).max().unwrap_or(0);
+        let v = Vec2{
+            x: 40 + (0.6 * size as f64 * chars as f64) as i32,
+            y: 8 + self.series.len() as i32 * (size + 6),
+        };
+        // small gap to the axes
+        let area = Frame{
+            p0: area.p0 + Vec2{x: 10, y: 10},
+            p1: area.p1 - Vec2{x: 10, y: 10},
+        };
+        let center = area.center();
+
+        match self.legend {
+            Some(PadAlignment::BottomLeft) => Frame{ p0: area.p0, p1: area.p0 + v },
+            Some(PadAlignment::Bottom) => Frame{
+                p0: Vec2{x: center.x - v.x/2, y: area.p0.y},
+                p1: Vec2{x: center.x + v.x/2, y: area.p0.y + v.y},
+            },
+            Some(PadAlignment::BottomRight) => Frame{
+                p0: Vec2{x: area.p1.x - v.x, y: area.p0.y},
+                p1: Vec2{x: area.p1.x, y: area.p0.y + v.y},
+            },
+            Some(PadAlignment::Right) => Frame{
+                p0: Vec2{x: area.p1.x - v.x, y: center.y - v.y/2},
+                p1: Vec2{x: area.p1.x, y: center.y + v.y/2},
+            },
+            Some(PadAlignment::Top) => Frame{
+                p0: Vec2{x: center.x - v.x/2, y: area.p1.y - v.y},
+                p1: Vec2{x: center.x + v.x/2, y: area.p1.y},
+            },
+            Some(PadAlignment::TopLeft) => Frame{
+                p0: Vec2{x: area.p0.x, y: area.p1.y - v.y},
+                p1: Vec2{x: area.p0.x + v.x, y: area.p1.y},
+            },
+            Some(PadAlignment::Left) => Frame{
+                p0: Vec2{x: area.p0.x, y: center.y - v.y/2},
+                p1: Vec2{x: area.p0.x + v.x, y: center.y + v.y/2},
+            },
+            Some(PadAlignment::Center) => Frame{ p0: center - v/2, p1: center + v/2 },
+            Some(PadAlignment::XY(ax, ay)) => {
+                let xx = match ax {
+                    Dim::Absolute(ix) => ix,
+                    Dim::Relative(fx) => (fx * area.width() as f64/2.0) as i32,
+                };
+                let yy = match ay {
+                    Dim::Absolute(iy) => iy,
+                    Dim::Relative(fy) => (fy * area.height() as f64/2.0) as i32,
+                };
+                let vv = Vec2{x: xx, y: yy};
+                Frame{ p0: center - v/2 + vv, p1: center + v/2 + vv }
+            },
+            Some(PadAlignment::TopRight) | None => Frame{ p0: area.p1 - v, p1: area.p1 },
+        }
+    }
+
+    fn build_text(
+        &self, ui: &mut conrod::UiCell, ressources: &WindowRessources,
+        id: conrod::widget::id::Id, text: &str, x: f64, y: f64
+    ) {
+        use conrod::{widget, Positionable, Colorable, Widget};
+
+        let mut label = widget::Text::new(text)
+            .x_y(x, y)
+            .color(fade(self.font.get_color(), self.opacity))
+            .font_size(self.font.get_size())
+            .floating(self.floating);
+        if let Some(fnt) = ressources.font(&self.font.get_font_id()) {
+            label = label.font_id(*fnt);
+        }
+        if let Some(ref ids) = self.ids {
+            label = label.parent(ids.plot);
+        }
+        label.set(id, ui);
+    }
+
+    fn build_axes(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
+        use conrod::{widget, Colorable, Widget};
+
+        let ids = match self.ids {
+            Some(ref ids) => ids,
+            None => return,
+        };
+
+        let gc = self.global_center;
+        let area = self.get_area();
+        let ((x0,x1),(y0,y1)) = self.get_spans();
+        let color = self.font.get_color();
+        let size = self.font.get_size() as f64;
+
+        let (l, r) = ((area.p0.x - gc.x) as f64, (area.p1.x - gc.x) as f64);
+        let (b, t) = ((area.p0.y - gc.y) as f64, (area.p1.y - gc.y) as f64);
+
+        widget::Line::abs([l, b], [r, b])
+            .color(fade(color, self.opacity))
+            .parent(ids.plot)
+            .set(ids.x_axis, ui);
+        widget::Line::abs([l, b], [l, t])
+            .color(fade(color, self.opacity))
+            .parent(ids.plot)
+            .set(ids.y_axis, ui);
+
+        // x ticks
+        let ticks = self.x_axis.ticks(x0, x1, area.width());
+        for (n, &(v, ref s)) in ticks.iter().enumerate() {
+            if n >= ids.x_ticks.len() || n >= ids.x_tick_labels.len() { break; }
+            let x = l + (v - x0) / (x1 - x0) * (r - l);
+            widget::Line::abs([x, b], [x, b - 5.0])
+                .color(fade(color, self.opacity))
+                .parent(ids.plot)
+                .set(ids.x_ticks[n], ui);
+            self.build_text(ui, ressources, ids.x_tick_labels[n], s, x, b - 8.0 - size/2.0);
+        }
+
+        // y ticks
+        let ticks = self.y_axis.ticks(y0, y1, area.height());
+        for (n, &(v, ref s)) in ticks.iter().enumerate() {
+            if n >= ids.y_ticks.len() || n >= ids.y_tick_labels.len() { break; }
+            let y = b + (v - y0) / (y1 - y0) * (t - b);
+            widget::Line::abs([l - 5.0, y], [l, y])
+                .color(fade(color, self.opacity))
+                .parent(ids.plot)
+                .set(ids.y_ticks[n], ui);
+            let w = 0.3 * size * s.chars().count() as f64;
+            self.build_text(ui, ressources, ids.y_tick_labels[n], s, l - 8.0 - w, y);
+        }
+
+        // axis labels
+        if !self.x_axis.label.is_empty() {
+            self.build_text(ui, ressources, ids.x_label, &self.x_axis.label,
+                (l + r)/2.0, (self.frame.p0.y - gc.y) as f64 + size/2.0);
+        }
+        if !self.y_axis.label.is_empty() {
+            self.build_text(ui, ressources, ids.y_label, &self.y_axis.label,
+                l, t + size/2.0);
+        }
+    }
+
+    fn build_series(&self, ui: &mut conrod::UiCell) {
+        use conrod::{widget, Colorable, Widget};
+
+        let ids = match self.ids {
+            Some(ref ids) => ids,
+            None => return,
+        };
+        let gc = self.global_center;
+        let area = self.get_area();
+        let spans = self.get_spans();
+
+        for (n, series) in self.series.iter().enumerate() {
+            if n >= ids.series.len() { break; }
+            let points: Vec<[f64; 2]> = series.points.iter()
+                .filter_map(|&(x,y)| self.project(area, spans, x, y))
+                .map(|p| [p.x - gc.x as f64, p.y - gc.y as f64])
+                .collect();
+            if points.len() < 2 { continue; }
+
+            widget::PointPath::abs(points)
+                .color(fade(series.color, self.opacity))
+                .thickness(series.thickness)
+                .parent(ids.area)
+                .set(ids.series[n], ui);
+        }
+    }
+}
+
+impl Plot {
+    fn build_annotations(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
+        use conrod::{widget, Colorable, Widget};
+
+        let ids = match self.ids {
+            Some(ref ids) => ids,
+            None => return,
+        };
+        let gc = Vec2{x: self.global_center.x as f64, y: self.global_center.y as f64};
+        let area = self.get_area();
+        let ((x0,x1),(y0,y1)) = self.get_spans();
+        let (l, r) = (area.p0.x as f64 - gc.x, area.p1.x as f64 - gc.x);
+        let (b, t) = (area.p0.y as f64 - gc.y, area.p1.y as f64 - gc.y);
+        let color = self.font.get_color();
+
+        let mut nline = 0;
+        let mut nhead = 0;
+        let mut ntext = 0;
+        for annotation in &self.annotations {
+            match *annotation {
+                Annotation::Text(x, y, ref text) => {
+                    if ntext >= ids.annotation_texts.len() { continue; }
+                    if let Some(p) = self.to_pixel(x, y) {
+                        self.build_text(ui, ressources, ids.annotation_texts[ntext],
+                            text, p.x - gc.x, p.y - gc.y);
+                        ntext += 1;
+                    }
+                },
+                Annotation::Arrow(xa, ya, xb, yb, ref text) => {
+                    if nline >= ids.annotation_lines.len()
+                        || nhead >= ids.annotation_heads.len()
+                        || ntext >= ids.annotation_texts.len() { continue; }
+                    if let (Some(pa), Some(pb)) = (self.to_pixel(xa, ya), self.to_pixel(xb, yb)) {
+                        let a = [pa.x - gc.x, pa.y - gc.y];
+                        let b = [pb.x - gc.x, pb.y - gc.y];
+                        widget::Line::abs(a, b)
+                            .color(fade(color, self.opacity))
+                            .parent(ids.area)
+                            .set(ids.annotation_lines[nline], ui);
+                        nline += 1;
+
+                        // arrow head
+                        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+                        let len = (dx*dx + dy*dy).sqrt();
+                        if len > 0.0 {
+                            let (ux, uy) = (dx/len * 10.0, dy/len * 10.0);
+                            let head = vec![
+                                b,
+                                [b[0] - ux - 0.5*uy, b[1] - uy + 0.5*ux],
+                                [b[0] - ux + 0.5*uy, b[1] - uy - 0.5*ux],
+                            ];
+                            widget::Polygon::abs_fill(head)
+                                .color(fade(color, self.opacity))
+                                .parent(ids.area)
+                                .set(ids.annotation_heads[nhead], ui);
+                            nhead += 1;
+                        }
+
+                        self.build_text(ui, ressources, ids.annotation_texts[ntext],
+                            text, a[0], a[1]);
+                        ntext += 1;
+                    }
+                },
+                Annotation::HorizontalLine(y, c) => {
+                    if nline >= ids.annotation_lines.len() || !self.y_axis.is_valid(y) { continue; }
+                    let yy = b + (self.y_axis.transform(y) - y0) / (y1 - y0) * (t - b);
+                    widget::Line::abs([l, yy], [r, yy])
+                        .color(fade(c, self.opacity))
+                        .parent(ids.area)
+                        .set(ids.annotation_lines[nline], ui);
+                    nline += 1;
+                },
+                Annotation::VerticalLine(x, c) => {
+                    if nline >= ids.annotation_lines.len() || !self.x_axis.is_valid(x) { continue; }
+                    let xx = l + (self.x_axis.transform(x) - x0) / (x1 - x0) * (r - l);
+                    widget::Line::abs([xx, b], [xx, t])
+                        .color(fade(c, self.opacity))
+                        .parent(ids.area)
+                        .set(ids.annotation_lines[nline], ui);
+                    nline += 1;
+                },
+            }
+        }
+    }
+
+    fn build_legend(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
+        use conrod::{widget, Colorable, Widget, Positionable};
+
+        let ids = match self.ids {
+            Some(ref ids) => ids,
+            None => return,
+        };
+        if self.legend.is_none() || self.series.is_empty() { return; }
+
+        let gc = self.global_center;
+        let frame = self.legend_frame();
+        let c = frame.center() - gc;
+        widget::Rectangle::fill_with(
+            [frame.width() as f64, frame.height() as f64],
+            fade(self.background, self.opacity)
+        ).x_y(c.x as f64, c.y as f64)
+            .parent(ids.plot)
+            .set(ids.legend, ui);
+
+        let size = self.font.get_size() as i32;
+        let x = (frame.p0.x - gc.x) as f64;
+        for (n, series) in self.series.iter().enumerate() {
+            if n >= ids.legend_lines.len() || n >= ids.legend_texts.len() { break; }
+            let y = (frame.p1.y - gc.y - 4 - n as i32 * (size + 6) - size/2) as f64;
+            widget::Line::abs([x + 6.0, y], [x + 26.0, y])
+                .color(fade(series.color, self.opacity))
+                .thickness(series.thickness)
+                .parent(ids.legend)
+                .set(ids.legend_lines[n], ui);
+
+            let w = 0.3 * size as f64 * series.name.chars().count() as f64;
+            self.build_text(ui, ressources, ids.legend_texts[n], &series.name, x + 34.0 + w, y);
+        }
+    }
+
+    fn build_crosshair(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
+        use conrod::{widget, Colorable, Widget, Positionable};
+
+        let ids = match self.ids {
+            Some(ref ids) => ids,
+            None => return,
+        };
+        if !self.crosshair { return; }
+        let (s, n) = match self.hover {
+            Some(hover) => hover,
+            None => return,
+        };
+        let (x, y) = self.series[s].points[n];
+        let p = match self.to_pixel(x, y) {
+            Some(p) => p,
+            None => return,
+        };
+
+        let gc = Vec2{x: self.global_center.x as f64, y: self.global_center.y as f64};
+        let area = self.get_area();
+        let p = p - gc;
+        let color = self.series[s].color;
+
+        widget::Line::abs([area.p0.x as f64 - gc.x, p.y], [area.p1.x as f64 - gc.x, p.y])
+            .color(fade(color.alpha(0.5), self.opacity))
+            .parent(ids.area)
+            .set(ids.crosshair_x, ui);
+        widget::Line::abs([p.x, area.p0.y as f64 - gc.y], [p.x, area.p1.y as f64 - gc.y])
+            .color(fade(color.alpha(0.5), self.opacity))
+            .parent(ids.area)
+            .set(ids.crosshair_y, ui);
+        widget::Circle::fill(4.0)
+            .x_y(p.x, p.y)
+            .color(fade(color, self.opacity))
+            .parent(ids.area)
+            .set(ids.crosshair_point, ui);
+
+        // tooltip
+        let text = format!("{}: x = {:.4}, y = {:.4}", self.series[s].name, x, y);
+        let size = self.font.get_size() as f64;
+        let w = 0.6 * size * text.chars().count() as f64 + 10.0;
+        let h = size + 10.0;
+        // keep the tooltip inside the plot
+        let tx = if p.x + 10.0 + w > area.p1.x as f64 - gc.x { p.x - 10.0 - w/2.0 } else { p.x + 10.0 + w/2.0 };
+        let ty = if p.y + 10.0 + h > area.p1.y as f64 - gc.y { p.y - 10.0 - h/2.0 } else { p.y + 10.0 + h/2.0 };
+        widget::Rectangle::fill_with([w, h], fade(self.background, self.opacity))
+            .x_y(tx, ty)
+            .parent(ids.plot)
+            .set(ids.tooltip, ui);
+        self.build_text(ui, ressources, ids.tooltip_text, &text, tx, ty);
+    }
+}
+
+/*
+d88888b db    db d8888b.  .d88b.  d8888b. d888888b
+88'     `8b  d8' 88  `8D .8P  Y8. 88  `8D `~~88~~'
+88ooooo  `8bd8'  88oodD' 88    88 88oobY'    88
+88~~~~~  .dPYb.  88~~~   88    88 88`8b      88
+88.     .8P  Y8. 88      `8b  d8' 88 `88.    88
+Y88888P YP    YP 88       `Y88P'  88   YD    YP
+
+
+*/
+
+
+
+// software renderer for exporting plots without a window.
+// Coordinates are pixel with the origin at the bottom left, like a Frame.
+struct Raster {
+    image: image::RgbaImage,
+}
+
+impl Raster {
+    fn new(width: u32, height: u32, background: conrod::Color) -> Self {
+        let conrod::color::Rgba(r,g,b,a) = background.to_rgb();
+        let px = image::Rgba([
+            (r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8, (a * 255.0) as u8
+        ]);
+        Raster {
+            image: image::RgbaImage::from_pixel(width, height, px)
+        }
+    }
+
+    fn blend(&mut self, x: i32, y: i32, color: conrod::Color, coverage: f32) {
+        let (w, h) = self.image.dimensions();
+        if x < 0 || y < 0 || x >= w as i32 || y >= h as i32 { return; }
+
+        let conrod::color::Rgba(r,g,b,a) = color.to_rgb();
+        let a = a * coverage;
+        let px = self.image.get_pixel_mut(x as u32, h - 1 - y as u32);
+        let src = [r, g, b];
+        for i in 0..3 {
+            let dst = px.data[i] as f32 / 255.0;
+            px.data[i] = ((src[i] * a + dst * (1.0 - a)) * 255.0) as u8;
+        }
+        let dst = px.data[3] as f32 / 255.0;
+        px.data[3] = ((a + dst * (1.0 - a)) * 255.0) as u8;
+    }
+
+    fn rect(&mut self, frame: Frame<i32>, color: conrod::Color) {
+        for y in frame.p0.y..frame.p1.y {
+            for x in frame.p0.x..frame.p1.x {
+                self.blend(x, y, color, 1.0);
+            }
+        }
+    }
+
+    fn line(&mut self, a: Vec2<f64>, b: Vec2<f64>, color: conrod::Color, thickness: f64) {
+        let d = b - a;
+        let len = (d.x*d.x + d.y*d.y).sqrt();
+        let r = thickness / 2.0;
+        let x0 = (a.x.min(b.x) - r).floor() as i32;
+        let x1 = (a.x.max(b.x) + r).ceil() as i32;
+        let y0 = (a.y.min(b.y) - r).floor() as i32;
+        let y1 = (a.y.max(b.y) + r).ceil() as i32;
+
+        for y in y0..(y1+1) {
+            for x in x0..(x1+1) {
+                let p = Vec2{x: x as f64 + 0.5, y: y as f64 + 0.5};
+                // distance of the pixel center to the segment
+                let t = if len > 0.0 {
+                    let t = ((p.x - a.x)*d.x + (p.y - a.y)*d.y) / (len*len);
+                    if t < 0.0 { 0.0 } else if t > 1.0 { 1.0 } else { t }
+                } else {
+                    0.0
+                };
+                let q = a + d * t;
+                let dist = ((p.x - q.x).powi(2) + (p.y - q.y).powi(2)).sqrt();
+                let coverage = r + 0.5 - dist;
+                if coverage > 0.0 {
+                    self.blend(x, y, color, if coverage > 1.0 { 1.0 } else { coverage as f32 });
+                }
+            }
+        }
+    }
+
+    fn polyline(&mut self, points: &[Vec2<f64>], color: conrod::Color, thickness: f64) {
+        for n in 1..points.len() {
+            self.line(points[n-1], points[n], color, thickness);
+        }
+    }
+
+    // text centered at (x,y)
+    fn text(&mut self, font: &conrod::text::Font, text: &str, x: f64, y: f64, size: u32, color: conrod::Color) {
+        use conrod::text::{Scale, rt};
+
+        let scale = Scale::uniform(size as f32);
+        let v = font.v_metrics(scale);
+        let h = self.image.height() as f32;
+
+        let glyphs: Vec<_> = font.layout(text, scale, rt::point(0.0, 0.0)).collect();
+        let width = glyphs.iter()
+            .filter_map(|g| g.pixel_bounding_box())
+            .map(|bb| bb.max.x)
+            .max()
+            .unwrap_or(0) as f64;
+
+        let x0 = (x - width/2.0) as i32;
+        let baseline = (h - y as f32 + (v.ascent + v.descent)/2.0) as i32;
+        for glyph in glyphs {
+            if let Some(bb) = glyph.pixel_bounding_box() {
+                glyph.draw(|gx, gy, coverage| {
+                    let ix = x0 + bb.min.x + gx as i32;
+                    let iy = baseline + bb.min.y + gy as i32;
+                    self.blend(ix, h as i32 - 1 - iy, color, coverage);
+                });
+            }
+        }
+    }
+}
+
+
+impl Plot {
+    /// writes all series as "series,x,y" rows.
+    pub fn export_csv<P>(&self, path: P) -> io::Result<()> where P: AsRef<Path> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "series,x,y")?;
+        for series in &self.series {
+            let name = if series.name.contains(',') || series.name.contains('"') {
+                format!("\"{}\"", series.name.replace("\"", "\"\""))
+            } else {
+                series.name.clone()
+            };
+            for &(x,y) in &series.points {
+                writeln!(file, "{},{},{}", name, x, y)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// renders the plot into an image file with the given resolution.
+    /// Works without a window, e.g. in batch jobs. The image has to be
+    /// larger than the margins around the plot area and at most 8192 pixel
+    /// wide and high.
+    pub fn export_png<P>(&self, path: P, width: u32, height: u32) -> io::Result<()> where P: AsRef<Path> {
+        let min_w = (MARGIN_LEFT + MARGIN_RIGHT) as u32;
+        let min_h = (MARGIN_BOTTOM + MARGIN_TOP) as u32;
+        if width <= min_w || height <= min_h {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!(
+                "image size {}x{} has to be larger than {}x{}", width, height, min_w, min_h
+            )));
+        }
+        if width > MAX_EXPORT_SIZE || height > MAX_EXPORT_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!(
+                "image size {}x{} is larger than {}x{}", width, height, MAX_EXPORT_SIZE, MAX_EXPORT_SIZE
+            )));
+        }
+        self.render(width, height).image.save(path)
+    }
+
+    fn render(&self, width: u32, height: u32) -> Raster {
+        let mut raster = Raster::new(width, height, self.background);
+        let frame = Frame::new_with_size(width as i32, height as i32);
+        let area = Self::area_of(frame);
+        let spans = self.get_spans();
+        let ((x0,x1),(y0,y1)) = spans;
+        let color = self.font.get_color();
+        let size = self.font.get_size();
+
+        let (l, r) = (area.p0.x as f64, area.p1.x as f64);
+        let (b, t) = (area.p0.y as f64, area.p1.y as f64);
+
+        // series, cropped to the plot area
+        {
+            let mut inner = Raster::new(area.width() as u32, area.height() as u32,
+                conrod::color::Color::Rgba(0.0,0.0,0.0,0.0));
+            let offset = Vec2{x: l, y: b};
+            for series in &self.series {
+                let points: Vec<Vec2<f64>> = series.points.iter()
+                    .filter_map(|&(x,y)| self.project(area, spans, x, y))
+                    .map(|p| p - offset)
+                    .collect();
+                inner.polyline(&points, series.color, series.thickness);
+            }
+            for annotation in &self.annotations {
+                match *annotation {
+                    Annotation::HorizontalLine(y, c) if self.y_axis.is_valid(y) => {
+                        let yy = (self.y_axis.transform(y) - y0) / (y1 - y0) * (t - b);
+                        inner.line(Vec2{x: 0.0, y: yy}, Vec2{x: r - l, y: yy}, c, 1.0);
+                    },
+                    Annotation::VerticalLine(x, c) if self.x_axis.is_valid(x) => {
+                        let xx = (self.x_axis.transform(x) - x0) / (x1 - x0) * (r - l);
+                        inner.line(Vec2{x: xx, y: 0.0}, Vec2{x: xx, y: t - b}, c, 1.0);
+                    },
+                    Annotation::Arrow(xa, ya, xb, yb, _) => {
+                        if let (Some(pa), Some(pb)) = (
+                            self.project(area, spans, xa, ya), self.project(area, spans, xb, yb)
+                        ) {
+                            inner.line(pa - offset, pb - offset, color, 1.0);
+                        }
+                    },
+                    _ => ()
+                }
+            }
+            let ih = inner.image.height();
+            for (x, y, px) in inner.image.enumerate_pixels() {
+                let c = conrod::color::Color::Rgba(
+                    px.data[0] as f32 / 255.0, px.data[1] as f32 / 255.0,
+                    px.data[2] as f32 / 255.0, 1.0
+                );
+                raster.blend(area.p0.x + x as i32, area.p0.y + (ih - 1 - y) as i32, c,
+                    px.data[3] as f32 / 255.0);
+            }
+        }
+
+        // axes
+        raster.line(Vec2{x: l, y: b}, Vec2{x: r, y: b}, color, 1.0);
+        raster.line(Vec2{x: l, y: b}, Vec2{x: l, y: t}, color, 1.0);
+
+        let xticks = self.x_axis.ticks(x0, x1, area.width());
+        let yticks = self.y_axis.ticks(y0, y1, area.height());
+        for &(v, _) in &xticks {
+            let x = l + (v - x0) / (x1 - x0) * (r - l);
+            raster.line(Vec2{x, y: b}, Vec2{x, y: b - 5.0}, color, 1.0);
+        }
+        for &(v, _) in &yticks {
+            let y = b + (v - y0) / (y1 - y0) * (t - b);
+            raster.line(Vec2{x: l - 5.0, y}, Vec2{x: l, y}, color, 1.0);
+        }
+
+        // legend
+        if self.legend.is_some() && !self.series.is_empty() {
+            let legend = self.legend_frame_in(area);
+            raster.rect(legend, self.background);
+            let s = size as i32;
+            for (n, series) in self.series.iter().enumerate() {
+                let y = (legend.p1.y - 4 - n as i32 * (s + 6) - s/2) as f64;
+                let x = legend.p0.x as f64;
+                raster.line(Vec2{x: x + 6.0, y}, Vec2{x: x + 26.0, y}, series.color, series.thickness);
+            }
+        }
+
+        // text
+        if let Some(ref font) = self.export_font {
+            let fs = size as f64;
+            for &(v, ref label) in &xticks {
+                let x = l + (v - x0) / (x1 - x0) * (r - l);
+                raster.text(font, label, x, b - 8.0 - fs/2.0, size, color);
+            }
+            for &(v, ref label) in &yticks {
+                let y = b + (v - y0) / (y1 - y0) * (t - b);
+                let w = 0.3 * fs * label.chars().count() as f64;
+                raster.text(font, label, l - 8.0 - w, y, size, color);
+            }
+            if !self.x_axis.label.is_empty() {
+                raster.text(font, &self.x_axis.label, (l + r)/2.0, fs/2.0, size, color);
+            }
+            if !self.y_axis.label.is_empty() {
+                raster.text(font, &self.y_axis.label, l, t + fs/2.0, size, color);
+            }
+            for annotation in &self.annotations {
+                match *annotation {
+                    Annotation::Text(x, y, ref text) | Annotation::Arrow(x, y, _, _, ref text) => {
+                        if let Some(p) = self.project(area, spans, x, y) {
+                            raster.text(font, text, p.x, p.y, size, color);
+                        }
+                    },
+                    _ => ()
+                }
+            }
+            if self.legend.is_some() {
+                let legend = self.legend_frame_in(area);
+                let s = size as i32;
+                for (n, series) in self.series.iter().enumerate() {
+                    let y = (legend.p1.y - 4 - n as i32 * (s + 6) - s/2) as f64;
+                    let w = 0.3 * fs * series.name.chars().count() as f64;
+                    raster.text(font, &series.name, legend.p0.x as f64 + 34.0 + w, y, size, color);
+                }
+            }
+        }
+
+        raster
+    }
+
+    fn export_failed(&self, path: &Path, e: io::Error) {
+        println!("Plot: could not export {:?}: {}", path, e);
+        self.send(ActionMsgData::ExportFailed(path.to_string_lossy().into_owned(), format!("{}", e)));
+    }
+
+    fn export(&self) {
+        if let Some((ref path, w, h)) = self.export {
+            let csv = path.with_extension("csv");
+            let png = path.with_extension("png");
+
+            match self.export_csv(&csv) {
+                Ok(()) => self.send(ActionMsgData::Text(csv.to_string_lossy().into_owned())),
+                Err(e) => self.export_failed(&csv, e),
+            }
+            match self.export_png(&png, w, h) {
+                Ok(()) => self.send(ActionMsgData::Text(png.to_string_lossy().into_owned())),
+                Err(e) => self.export_failed(&png, e),
+            }
+        }
+    }
+}
+
+
+
+
+
+
+
+
+
+impl ActionSendable for Plot {
+    fn with_id(mut self, id: String) -> Box<Self> {
+        self.id = id;
+        Box::new(self)
+    }
+    fn with_sender(mut self, sender: Sender<ActionMsg>) -> Box<Self> {
+        self.senders.push(sender);
+        Box::new(self)
+    }
+}
+
+impl Labelable for Plot {
+    fn with_font(mut self, font: Font) -> Box<Self> {
+        self.font = font;
+        Box::new(self)
+    }
+    fn set_font(&mut self, font: Font) {
+        self.font = font;
+    }
+}
+
+impl Element for Plot {
+    fn setup(&mut self, ui: &mut conrod::Ui) {
+        // ids allocated before are kept, the lists only grow with more series or annotations
+        let mut ids = match self.ids.take() {
+            Some(ids) => ids,
+            None => {
+                let mut ids = PlotIds::new(ui.widget_id_generator());
+                ids.x_ticks.resize(MAX_TICKS, &mut ui.widget_id_generator());
+                ids.x_tick_labels.resize(MAX_TICKS, &mut ui.widget_id_generator());
+                ids.y_ticks.resize(MAX_TICKS, &mut ui.widget_id_generator());
+                ids.y_tick_labels.resize(MAX_TICKS, &mut ui.widget_id_generator());
+                ids
+            },
+        };
+        if ids.series.len() < self.series.len() {
+            ids.series.resize(self.series.len(), &mut ui.widget_id_generator());
+            ids.legend_lines.resize(self.series.len(), &mut ui.widget_id_generator());
+            ids.legend_texts.resize(self.series.len(), &mut ui.widget_id_generator());
+        }
+        if ids.annotation_lines.len() < self.annotations.len() {
+            ids.annotation_lines.resize(self.annotations.len(), &mut ui.widget_id_generator());
+            ids.annotation_heads.resize(self.annotations.len(), &mut ui.widget_id_generator());
+            ids.annotation_texts.resize(self.annotations.len(), &mut ui.widget_id_generator());
+        }
+        self.ids = Some(ids);
+        self.is_setup = true;
+        if DEBUG { println!("Plot --- setup()"); }
+    }
+    fn is_setup(&self) -> bool {
+        self.is_setup && self.ids.as_ref().map_or(false, |ids| {
+            ids.series.len() >= self.series.len()
+                && ids.annotation_lines.len() >= self.annotations.len()
+        })
+    }
+
+    fn set_parent_widget(&mut self, parent: conrod::widget::id::Id) {
+        self.parent = Some(parent);
+    }
+    fn set_floating(&mut self, floating: bool) {
+        self.floating = floating;
+    }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        self.font.apply_theme(theme);
+        self.font.apply_style(&theme.style("Plot", &self.id, &[]));
+    }
+
+    fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
+        use conrod::{widget, Widget, Positionable};
+
+        if let Some(ref ids) = self.ids {
+            let c = self.frame.center() - self.global_center;
+            let mut rect = widget::Rectangle::fill_with(
+                [self.frame.width() as f64, self.frame.height() as f64],
+                fade(self.background, self.opacity)
+            ).x_y(c.x as f64, c.y as f64).floating(self.floating);
+            if let Some(parent) = self.parent {
+                rect = rect.parent(parent);
+            }
+            rect.set(ids.plot, ui);
+
+            // data is cropped to the plot area
+            let area = self.get_area();
+            let c = area.center() - self.global_center;
+            widget::Rectangle::fill_with(
+                [area.width() as f64, area.height() as f64],
+                conrod::color::Color::Rgba(0.0,0.0,0.0,0.0)
+            ).x_y(c.x as f64, c.y as f64)
+                .crop_kids()
+                .parent(ids.plot)
+                .set(ids.area, ui);
+        }
+
+        self.build_axes(ui, ressources);
+        self.build_series(ui);
+        self.build_annotations(ui, ressources);
+        self.build_crosshair(ui, ressources);
+        self.build_legend(ui, ressources);
+    }
+
+    fn get_frame(&self) -> Frame<i32> {
+        self.frame
+    }
+    fn set_frame(&mut self, frame: Frame<i32>, window_center: Vec2<i32>) {
+        self.frame = frame;
+        self.global_center = window_center;
+    }
+
+    fn set_min_size(&mut self, size: Vec2<i32>) {
+        self.min_size = size;
+    }
+    fn get_min_size(&self) -> Vec2<i32> {
+        self.min_size
+    }
+    fn set_max_size(&mut self, size: Vec2<i32>) {
+        self.max_size = size;
+    }
+    fn get_max_size(&self) -> Vec2<i32> {
+        self.max_size
+    }
+
+    fn transmit_msg(&mut self, msg: ActionMsg, _stop: bool) {
+        match msg.msg {
+            ActionMsgData::Mouse(x,y) => {
+                if !self.crosshair { return; }
+
+                let hover = if self.get_area().inside(x as i32, y as i32) {
+                    self.find_nearest(x, y)
+                } else {
+                    None
+                };
+                if hover != self.hover {
+                    self.hover = hover;
+                    if let Some((name, px, py)) = self.get_hover() {
+                        self.send(ActionMsgData::PlotHover(name, px, py));
+                    }
+                }
+            },
+            ActionMsgData::MousePressRight(x,y) => {
+                if self.frame.inside(x as i32, y as i32) {
+                    self.export();
+                }
+            },
+            _ => ()
+        }
+    }
+}
@@ -1,6 +1,8 @@
 
 
 use elements::*;
+use elements::theme::*;
+use elements::style::*;
 use conrod;
 
 
@@ -40,12 +42,28 @@ YP       `Y88P'  VP   V8P    YP
 
 */
 
+/// Returned when adding a font to the window, refers to it by its id.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FontHandle(String);
+
+impl FontHandle {
+    pub(crate) fn new(id: String) -> Self {
+        FontHandle(id)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.0
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Font {
     text: String,
     size: u32,
     font_id: String,
     color: conrod::Color,
+    // font, size and color are taken from the theme
+    themed: bool,
 }
 
 impl Font {
@@ -54,10 +72,46 @@ impl Font {
             text: "".to_string(),
             size,
             font_id,
-            color
+            color,
+            themed: false,
+        }
+    }
+
+    /// font, size and color of the window's theme, also after switching it.
+    pub fn themed() -> Self {
+        let theme = Theme::default();
+        Font {
+            themed: true,
+            ..Font::new(theme.font_id, theme.font_size, theme.text)
+        }
+    }
+
+    pub fn is_themed(&self) -> bool {
+        self.themed
+    }
+
+    /// takes font, size and color from the theme if the font is themed.
+    pub fn apply_theme(&mut self, theme: &Theme) {
+        if self.themed {
+            self.font_id = theme.font_id.clone();
+            self.size = theme.font_size;
+            self.color = theme.text;
         }
     }
 
+    /// like apply_theme, for the values set in the style.
+    pub fn apply_style(&mut self, style: &Style) {
+        if self.themed {
+            if let Some(ref font_id) = style.font_id { self.font_id = font_id.clone(); }
+            if let Some(size) = style.font_size { self.size = size; }
+            if let Some(color) = style.text { self.color = color; }
+        }
+    }
+
+    pub fn from_handle(handle: &FontHandle, size: u32, color: conrod::Color) -> Self {
+        Font::new(handle.id().to_string(), size, color)
+    }
+
     pub fn write(&self, text: String) -> Self {
         let mut res = self.clone();
         res.text = text;
@@ -130,6 +184,9 @@ d888888b d88888b db    db d888888b db    db d8888b. d88888b
 
 */
 
+/// most pieces a tiled texture is drawn with, larger frames scale the tiles up.
+pub const MAX_TILES: usize = 256;
+
 #[derive(Debug, Copy, Clone)]
 pub enum TextureMode {
     Stretch,
@@ -137,7 +194,25 @@ pub enum TextureMode {
     FitHeight,
     FitMin,
     FitMax,
+    /// repeats the image at its pixel size, starting at the top left.
     Tile,
+    /// insets (left, top, right, bottom) in image pixels. The corners keep their
+    /// pixel size, the borders are stretched along one axis and the center along both.
+    NineSlice(u32, u32, u32, u32),
+}
+
+/// Returned when adding an image to the window, refers to it by its id.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImageHandle(String);
+
+impl ImageHandle {
+    pub(crate) fn new(id: String) -> Self {
+        ImageHandle(id)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.0
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -145,6 +220,8 @@ pub struct Texture {
     id: String,
     cut: Option<Frame<u32>>,
     mode: TextureMode,
+    // drawn while the image is not available, e.g. still loading
+    placeholder: Option<Box<Graphic>>,
 }
 
 impl Texture {
@@ -153,13 +230,23 @@ impl Texture {
             id,
             cut: None,
             mode: TextureMode::Stretch,
+            placeholder: None,
         }
     }
 
+    pub fn from_handle(handle: &ImageHandle) -> Self {
+        Texture::new(handle.id().to_string())
+    }
+
     pub fn get_id(&self) -> String {
         self.id.clone()
     }
 
+    pub fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+
+    /// restricts the texture to a region of the image, in pixels from the top left.
     pub fn set_cut(&mut self, cut: Frame<u32>) {
         self.cut = Some(cut);
     }
@@ -169,6 +256,19 @@ impl Texture {
         self
     }
 
+    /// drawn instead while the image is not available, e.g. during add_image_async.
+    pub fn with_placeholder(mut self, placeholder: Graphic) -> Self {
+        self.placeholder = Some(Box::new(placeholder));
+        self
+    }
+
+    pub fn get_placeholder(&self) -> Option<&Graphic> {
+        match self.placeholder {
+            Some(ref placeholder) => Some(placeholder),
+            None => None,
+        }
+    }
+
     pub fn set_mode(&mut self, mode: TextureMode) {
         self.mode = mode;
     }
@@ -178,48 +278,144 @@ impl Texture {
         self
     }
 
+    /// number of pieces the texture is drawn with at most.
+    pub fn max_pieces(&self) -> usize {
+        match self.mode {
+            TextureMode::Tile => MAX_TILES,
+            TextureMode::NineSlice(..) => 9,
+            _ => 1,
+        }
+    }
+
+    // the used region of the image (x, y, width, height) with the origin at the bottom left
+    fn region(&self, img_w: u32, img_h: u32) -> (f64, f64, f64, f64) {
+        // the cut is given in image pixels from the top left, the texture starts at the bottom left
+        match self.cut {
+            Some(c) => {
+                // corners in any order
+                let (x0, x1) = (c.p0.x.min(c.p1.x).min(img_w), c.p0.x.max(c.p1.x).min(img_w));
+                let (y0, y1) = (c.p0.y.min(c.p1.y).min(img_h), c.p0.y.max(c.p1.y).min(img_h));
+                (x0 as f64, (img_h - y1) as f64, (x1 - x0) as f64, (y1 - y0) as f64)
+            },
+            None => (0.0, 0.0, img_w as f64, img_h as f64),
+        }
+    }
+
     pub fn get_cut(&self, w: u32,h: u32, img_w: u32, img_h: u32) -> Rect {
-        //println!("get_cut() {} {} {} {}", w, h, img_w, img_h);
-
-        // TODO implement pre-defined texture cut //////////////////////////////////////////////////
-        //let mut tex_cut = match self.cut {
-        //    Some(c) => c,
-        //    None => Frame::new()
-        //};
-//
+        let (x0, y0, cut_w, cut_h) = self.region(img_w, img_h);
+        let cut = |cw: f64, ch: f64| Rect::from_corners([x0, y0], [x0 + cw, y0 + ch]);
+
         let ratio = w as f64 / h as f64;
-        //let (min, max) = if w > h {(h,w)} else {(w,h)};
-//
-        //let img_ratio = img_w as f64 / img_h as f64;
-        //let (img_min, img_max) = if img_w > img_h {(img_h, img_w)} else {(img_w, img_h)};
 
         match self.mode {
             TextureMode::FitHeight => {
-                Rect::from_corners([0.0,0.0], [ratio * img_w as f64, img_h as f64])
+                cut(ratio * cut_w, cut_h)
             },
             TextureMode::FitWidth => {
-                Rect::from_corners([0.0,0.0], [img_w as f64, img_h as f64 / ratio])
+                cut(cut_w, cut_h / ratio)
             },
             TextureMode::FitMax => {
                 if ratio > 1.0 {
-                    Rect::from_corners([0.0,0.0], [img_w as f64, img_h as f64 / ratio])
+                    cut(cut_w, cut_h / ratio)
                 } else {
-                    Rect::from_corners([0.0,0.0], [ratio * img_w as f64, img_h as f64])
+                    cut(ratio * cut_w, cut_h)
                 }
             },
             TextureMode::FitMin => {
                 if ratio < 1.0 {
-                    Rect::from_corners([0.0,0.0], [img_w as f64, img_h as f64 / ratio])
+                    cut(cut_w, cut_h / ratio)
                 } else {
-                    Rect::from_corners([0.0,0.0], [ratio * img_w as f64, img_h as f64])
+                    cut(ratio * cut_w, cut_h)
                 }
             },
             _ => {
-                println!("get_cut() Stretch");
-                Rect::from_corners([0.0, 0.0], [img_w as f64, img_h as f64])
+                cut(cut_w, cut_h)
             },
         }
     }
+
+    /// pairs of source rectangles in the image and destination rectangles relative
+    /// to the center of a `w` x `h` frame. Tile and NineSlice consist of several pieces.
+    pub fn get_pieces(&self, w: u32,h: u32, img_w: u32, img_h: u32) -> Vec<(Rect, Rect)> {
+        let (w, h) = (w as f64, h as f64);
+        match self.mode {
+            TextureMode::Tile => self.tile_pieces(w, h, img_w, img_h),
+            TextureMode::NineSlice(l, t, r, b) => self.nine_slice_pieces(w, h, img_w, img_h, (l, t, r, b)),
+            _ => vec![(
+                self.get_cut(w as u32, h as u32, img_w, img_h),
+                Rect::from_corners([-w / 2.0, -h / 2.0], [w / 2.0, h / 2.0])
+            )],
+        }
+    }
+
+    fn tile_pieces(&self, w: f64, h: f64, img_w: u32, img_h: u32) -> Vec<(Rect, Rect)> {
+        let (x0, y0, cut_w, cut_h) = self.region(img_w, img_h);
+        if cut_w <= 0.0 || cut_h <= 0.0 { return Vec::new() }
+
+        // scale the tiles up if there would be too many of them
+        let mut scale = 1.0;
+        while ((w / (cut_w * scale)).ceil() * (h / (cut_h * scale)).ceil()) as usize > MAX_TILES {
+            scale += 1.0;
+        }
+        let (tile_w, tile_h) = (cut_w * scale, cut_h * scale);
+
+        let mut pieces = Vec::new();
+        let mut top = h / 2.0;
+        while top > -h / 2.0 {
+            let bottom = (top - tile_h).max(-h / 2.0);
+            let mut left = -w / 2.0;
+            while left < w / 2.0 {
+                let right = (left + tile_w).min(w / 2.0);
+                // cut tiles at the right and bottom show the top left part of the image
+                let (sw, sh) = ((right - left) / scale, (top - bottom) / scale);
+                pieces.push((
+                    Rect::from_corners([x0, y0 + cut_h - sh], [x0 + sw, y0 + cut_h]),
+                    Rect::from_corners([left, bottom], [right, top])
+                ));
+                left = right;
+            }
+            top = bottom;
+        }
+        pieces
+    }
+
+    fn nine_slice_pieces(
+        &self,
+        w: f64, h: f64,
+        img_w: u32, img_h: u32,
+        insets: (u32, u32, u32, u32)
+    ) -> Vec<(Rect, Rect)> {
+        let (x0, y0, cut_w, cut_h) = self.region(img_w, img_h);
+        let (l, t, r, b) = (insets.0 as f64, insets.1 as f64, insets.2 as f64, insets.3 as f64);
+        // insets larger than the image are reduced to fit
+        let fit = |a: f64, b: f64, size: f64| if a + b > size && a + b > 0.0 {
+            (a * size / (a + b), b * size / (a + b))
+        } else {
+            (a, b)
+        };
+        let (l, r) = fit(l, r, cut_w);
+        let (b, t) = fit(b, t, cut_h);
+        // the borders shrink if the frame is smaller than them
+        let (dl, dr) = fit(l, r, w);
+        let (db, dt) = fit(b, t, h);
+
+        let src_x = [x0, x0 + l, x0 + cut_w - r, x0 + cut_w];
+        let src_y = [y0, y0 + b, y0 + cut_h - t, y0 + cut_h];
+        let dst_x = [-w / 2.0, -w / 2.0 + dl, w / 2.0 - dr, w / 2.0];
+        let dst_y = [-h / 2.0, -h / 2.0 + db, h / 2.0 - dt, h / 2.0];
+
+        let mut pieces = Vec::new();
+        for j in 0..3 {
+            for i in 0..3 {
+                if dst_x[i + 1] - dst_x[i] <= 0.0 || dst_y[j + 1] - dst_y[j] <= 0.0 { continue }
+                pieces.push((
+                    Rect::from_corners([src_x[i], src_y[j]], [src_x[i + 1], src_y[j + 1]]),
+                    Rect::from_corners([dst_x[i], dst_y[j]], [dst_x[i + 1], dst_y[j + 1]])
+                ));
+            }
+        }
+        pieces
+    }
 }
 
 
@@ -0,0 +1,474 @@
+use conrod;
+
+use elements::{*, action::*, theme::*};
+use std::sync::Arc;
+use std::i32;
+
+
+
+
+
+
+
+
+/*
+.d8888. d8888b. d8888b. d888888b d8b   db  d888b
+88'  YP 88  `8D 88  `8D   `88'   888o  88 88' Y8b
+`8bo.   88oodD' 88oobY'    88    88V8o 88 88
+  `Y8b. 88~~~   88`8b      88    88 V8o88 88  ooo
+db   8D 88      88 `88.   .88.   88  V888 88. ~8~
+`8888Y' 88      88   YD Y888888P VP   V8P  Y888P
+
+
+*/
+
+
+
+
+// largest integration step in seconds, larger steps are split up
+const MAX_STEP: f64 = 0.001;
+// a spring is at rest if it is closer to the target (px) and slower (px/s) than this
+const REST_DISTANCE: f64 = 0.5;
+const REST_VELOCITY: f64 = 5.0;
+
+
+
+
+/// Parameters of a damped spring.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Spring {
+    pub stiffness: f64,
+    pub damping: f64,
+    pub mass: f64,
+}
+
+impl Spring {
+    pub fn new(stiffness: f64, damping: f64, mass: f64) -> Self {
+        Spring { stiffness, damping, mass }
+    }
+
+    pub fn gentle() -> Self {
+        Spring::new(120.0, 14.0, 1.0)
+    }
+
+    pub fn wobbly() -> Self {
+        Spring::new(180.0, 12.0, 1.0)
+    }
+
+    pub fn stiff() -> Self {
+        Spring::new(210.0, 20.0, 1.0)
+    }
+}
+
+impl Default for Spring {
+    fn default() -> Self {
+        Spring::new(170.0, 26.0, 1.0)
+    }
+}
+
+
+
+/// One value moved by a spring. Changing the target keeps position and
+/// velocity, so the motion can be retargeted mid-flight without a jump.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpringValue {
+    pub value: f64,
+    pub velocity: f64,
+    pub target: f64,
+}
+
+impl SpringValue {
+    pub fn new(value: f64) -> Self {
+        SpringValue {
+            value,
+            velocity: 0.0,
+            target: value,
+        }
+    }
+
+    pub fn set_target(&mut self, target: f64) {
+        self.target = target;
+    }
+
+    /// jumps to `value` and stays there.
+    pub fn set_value(&mut self, value: f64) {
+        self.value = value;
+        self.target = value;
+        self.velocity = 0.0;
+    }
+
+    pub fn is_at_rest(&self) -> bool {
+        (self.value - self.target).abs() < REST_DISTANCE && self.velocity.abs() < REST_VELOCITY
+    }
+
+    /// advances by `dt` seconds.
+    pub fn step(&mut self, spring: &Spring, dt: f64) {
+        if self.is_at_rest() {
+            self.value = self.target;
+            self.velocity = 0.0;
+            return
+        }
+
+        let mut rem = dt;
+        while rem > 0.0 {
+            let h = if rem < MAX_STEP { rem } else { MAX_STEP };
+            let force = -spring.stiffness * (self.value - self.target) - spring.damping * self.velocity;
+            // semi-implicit Euler
+            self.velocity += force / spring.mass * h;
+            self.value += self.velocity * h;
+            rem -= h;
+        }
+    }
+}
+
+
+
+
+
+
+
+
+
+
+/*
+.d8888. d8888b. d8888b. d888888b d8b   db  d888b      .d8b.  d8b   db d888888b .88b  d88.  .d8b.  d888888b d888888b  .d88b.  d8b   db
+88'  YP 88  `8D 88  `8D   `88'   888o  88 88' Y8b    d8' `8b 888o  88   `88'   88'YbdP`88 d8' `8b `~~88~~'   `88'   .8P  Y8. 888o  88
+`8bo.   88oodD' 88oobY'    88    88V8o 88 88         88ooo88 88V8o 88    88    88  88  88 88ooo88    88       88    88    88 88V8o 88
+  `Y8b. 88~~~   88`8b      88    88 V8o88 88  ooo    88~~~88 88 V8o88    88    88  88  88 88~~~88    88       88    88    88 88 V8o88
+db   8D 88      88 `88.   .88.   88  V888 88. ~8~    88   88 88  V888   .88.   88  88  88 88   88    88      .88.   `8b  d8' 88  V888
+`8888Y' 88      88   YD Y888888P VP   V8P  Y888P     YP   YP VP   V8P Y888888P YP  YP  YP YP   YP    YP    Y888888P  `Y88P'  VP   V8P
+
+
+*/
+
+
+
+
+widget_ids!(
+    #[derive(Clone)]
+    struct SpringAnimationIds {
+        spring,
+    }
+);
+
+
+/// Moves the size and position of an element with springs instead of a fixed
+/// duration. Size and position are offsets in pixels from the original layout,
+/// (0,0) is the resting state in which the element is reset.
+pub struct SpringAnimation {
+    ids: Option<SpringAnimationIds>,
+    parent: Option<conrod::widget::id::Id>,
+    floating: bool,
+
+    pub element: Box<Animateable>,
+
+    spring: Spring,
+    size: (SpringValue, SpringValue),
+    position: (SpringValue, SpringValue),
+
+    time_source: Arc<TimeSource>,
+    last_time: Option<u64>,
+    active: bool,
+
+    min_size: Vec2<i32>,
+    max_size: Vec2<i32>,
+}
+
+impl SpringAnimation {
+    pub fn new(element: Box<Animateable>, spring: Spring) -> Box<Self> {
+        Box::new(SpringAnimation {
+            ids: None,
+            parent: None,
+            floating: false,
+            element,
+            spring,
+            size: (SpringValue::new(0.0), SpringValue::new(0.0)),
+            position: (SpringValue::new(0.0), SpringValue::new(0.0)),
+            time_source: Arc::new(PreciseTime),
+            last_time: None,
+            active: false,
+            min_size: Vec2::zero(),
+            max_size: Vec2 {x: i32::MAX, y: i32::MAX},
+        })
+    }
+
+    pub fn with_time_source(mut self, time_source: Arc<TimeSource>) -> Box<Self> {
+        self.time_source = time_source;
+        Box::new(self)
+    }
+
+    pub fn with_floating(mut self, floating: bool) -> Box<Self> {
+        self.floating = floating;
+        Box::new(self)
+    }
+
+    pub fn set_spring(&mut self, spring: Spring) {
+        self.spring = spring;
+    }
+
+    /// lets the size spring towards the given offset.
+    pub fn set_size_target(&mut self, x: f64, y: f64) {
+        self.wake_up();
+        self.size.0.set_target(x);
+        self.size.1.set_target(y);
+        self.activate();
+    }
+
+    /// lets the position spring towards the given offset.
+    pub fn set_position_target(&mut self, x: f64, y: f64) {
+        self.wake_up();
+        self.position.0.set_target(x);
+        self.position.1.set_target(y);
+        self.activate();
+    }
+
+    /// moves the element immediately, e.g. while it's dragged.
+    pub fn set_position(&mut self, x: f64, y: f64) {
+        self.position.0.set_value(x);
+        self.position.1.set_value(y);
+        self.activate();
+        self.apply();
+    }
+
+    /// springs back into the original layout.
+    pub fn snap_back(&mut self) {
+        self.set_size_target(0.0, 0.0);
+        self.set_position_target(0.0, 0.0);
+    }
+
+    pub fn is_at_rest(&self) -> bool {
+        self.size.0.is_at_rest() && self.size.1.is_at_rest()
+            && self.position.0.is_at_rest() && self.position.1.is_at_rest()
+    }
+
+    fn is_home(&self) -> bool {
+        self.size.0.target == 0.0 && self.size.1.target == 0.0
+            && self.position.0.target == 0.0 && self.position.1.target == 0.0
+    }
+
+    // no frames are requested while at rest, so the time since the last one is meaningless
+    fn wake_up(&mut self) {
+        if self.is_at_rest() {
+            self.last_time = None;
+        }
+    }
+
+    fn activate(&mut self) {
+        if !self.active {
+            self.active = true;
+            self.last_time = None;
+            // TODO not ideal. need to manage individual floating property
+            self.element.set_floating(self.floating);
+            self.element.start();
+        }
+    }
+
+    fn apply(&mut self) {
+        self.element.animate_size((
+            Dim::Absolute(self.size.0.value.round() as i32),
+            Dim::Absolute(self.size.1.value.round() as i32)
+        ));
+        self.element.animate_position((
+            Dim::Absolute(self.position.0.value.round() as i32),
+            Dim::Absolute(self.position.1.value.round() as i32)
+        ));
+    }
+}
+
+
+impl Animateable for SpringAnimation {
+    fn animate_size(&mut self, xy: (Dim,Dim)) {
+        self.element.animate_size(xy);
+    }
+    fn animate_position(&mut self, xy: (Dim,Dim)) {
+        self.element.animate_position(xy);
+    }
+    fn animate_color(&mut self, color: conrod::Color) {
+        self.element.animate_color(color);
+    }
+    fn animate_alpha(&mut self, alpha: f32) {
+        self.element.animate_alpha(alpha);
+    }
+
+    fn run(&mut self) {
+        if !self.active { return }
+
+        let now = self.time_source.now_ns();
+        let dt = match self.last_time {
+            Some(last) => now.saturating_sub(last) as f64 * 1e-9,
+            None => 0.0,
+        };
+        self.last_time = Some(now);
+
+        let spring = self.spring;
+        self.size.0.step(&spring, dt);
+        self.size.1.step(&spring, dt);
+        self.position.0.step(&spring, dt);
+        self.position.1.step(&spring, dt);
+        self.apply();
+
+        if self.is_at_rest() && self.is_home() {
+            self.reset();
+        }
+    }
+
+    fn reset(&mut self) {
+        self.active = false;
+        self.size = (SpringValue::new(0.0), SpringValue::new(0.0));
+        self.position = (SpringValue::new(0.0), SpringValue::new(0.0));
+        // TODO not ideal. need to manage individual floating property
+        self.element.set_floating(false);
+        self.element.reset();
+    }
+}
+
+
+impl Element for SpringAnimation {
+    fn setup(&mut self, ui: &mut conrod::Ui) {
+        self.ids = Some(SpringAnimationIds::new(ui.widget_id_generator()));
+        self.element.setup(ui);
+    }
+    fn is_setup(&self) -> bool {
+        self.element.is_setup()
+    }
+
+    fn set_floating(&mut self, floating: bool) {
+        self.element.set_floating(floating);
+    }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.element.set_opacity(opacity);
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        self.element.set_theme(theme);
+    }
+
+    fn set_parent_widget(&mut self, parent: conrod::widget::id::Id) {
+        self.parent = Some(parent);
+        self.element.set_parent_widget(parent);
+    }
+
+    fn stop(&mut self) {
+        self.element.stop();
+        self.active = false;
+    }
+    fn is_animating(&self) -> bool {
+        (self.active && !self.is_at_rest()) || self.element.is_animating()
+    }
+    fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
+        self.element.build_window(ui, ressources);
+    }
+
+    fn get_frame(&self) -> Frame<i32> {
+        self.element.get_frame()
+    }
+    fn set_frame(&mut self, frame: Frame<i32>, window_center: Vec2<i32>) {
+        self.element.set_frame(frame, window_center);
+    }
+
+    fn set_max_size(&mut self, size: Vec2<i32>) {
+        self.max_size = size;
+    }
+    fn set_min_size(&mut self, size: Vec2<i32>) {
+        self.min_size = size;
+    }
+
+    fn get_min_size(&self) -> Vec2<i32> {
+        self.element.get_min_size()
+    }
+    fn get_max_size(&self) -> Vec2<i32> {
+        self.element.get_max_size()
+    }
+    fn transmit_msg(&mut self, msg: ActionMsg, stop: bool) {
+        match msg.msg {
+            ActionMsgData::Update => {
+                self.run();
+            },
+            _ => ()
+        }
+
+        if !stop {
+            self.element.transmit_msg(msg, false);
+        }
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use elements::action::tests::Probe;
+
+    // runs the animation with 60 fps for `ms`
+    fn play(animation: &mut SpringAnimation, time: &ManualTime, ms: u32) {
+        for _ in 0..ms/16 {
+            time.advance_ms(16.0);
+            animation.run();
+        }
+    }
+
+    #[test]
+    fn springs_to_the_target_and_back() {
+        let time = ManualTime::new();
+        let (probe, log) = Probe::new();
+        let mut animation = SpringAnimation::new(probe, Spring::default())
+            .with_time_source(Arc::new(time.clone()));
+
+        animation.set_position_target(100.0, 0.0);
+        assert!(animation.is_animating());
+        // the first frame after waking up doesn't move
+        time.advance_ms(1000.0);
+        animation.run();
+        assert_eq!(log.borrow().position, Some((0, 0)));
+
+        play(&mut animation, &time, 100);
+        let x = log.borrow().position.unwrap().0;
+        assert!(x > 0 && x < 100);
+
+        play(&mut animation, &time, 3000);
+        assert!(animation.is_at_rest());
+        assert!(!animation.is_animating());
+        assert_eq!(log.borrow().position, Some((100, 0)));
+        assert_eq!(log.borrow().resets, 0);
+
+        animation.snap_back();
+        play(&mut animation, &time, 3000);
+        assert_eq!(log.borrow().position, Some((0, 0)));
+        assert_eq!(log.borrow().resets, 1);
+    }
+
+    #[test]
+    fn spring_waits_when_the_time_goes_back() {
+        let time = ManualTime::new();
+        let (probe, log) = Probe::new();
+        let mut animation = SpringAnimation::new(probe, Spring::default())
+            .with_time_source(Arc::new(time.clone()));
+
+        animation.set_position_target(100.0, 0.0);
+        time.set_ms(1000.0);
+        animation.run();
+        play(&mut animation, &time, 100);
+        let before = log.borrow().position;
+
+        time.set_ms(500.0);
+        animation.run();
+        assert_eq!(log.borrow().position, before);
+        assert!(animation.is_animating());
+    }
+
+    #[test]
+    fn spring_value_is_independent_of_the_frame_rate() {
+        let spring = Spring::default();
+        let mut coarse = SpringValue::new(0.0);
+        let mut fine = SpringValue::new(0.0);
+        coarse.set_target(100.0);
+        fine.set_target(100.0);
+
+        coarse.step(&spring, 0.1);
+        for _ in 0..100 {
+            fine.step(&spring, 0.001);
+        }
+        assert!((coarse.value - fine.value).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,311 @@
+use conrod;
+
+use elements::{*, action::*, basic::*};
+use std::sync::Arc;
+use std::sync::mpsc::Sender;
+
+
+
+
+
+
+
+
+/*
+.d8888. d8888b. d8888b. d888888b d888888b d88888b
+88'  YP 88  `8D 88  `8D   `88'   `~~88~~' 88'
+`8bo.   88oodD' 88oobY'    88       88    88ooooo
+  `Y8b. 88~~~   88`8b      88       88    88~~~~~
+db   8D 88      88 `88.   .88.      88    88.
+`8888Y' 88      88   YD Y888888P    YP    Y88888P
+
+
+*/
+
+
+
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpriteMode {
+    Loop,
+    /// stops on the last frame and sends Finished.
+    Once,
+}
+
+
+
+/// Steps through the cells of a sprite sheet, a grid of equally sized frames,
+/// left to right and top to bottom. The texture mode is applied to every frame.
+/// Listens to Play, Pause and Seek with its id.
+pub struct Sprite {
+    id: String,
+    senders: Vec<Sender<ActionMsg>>,
+
+    plane: Box<Plane>,
+    texture: Texture,
+    columns: u32,
+    rows: u32,
+    frames: usize,
+
+    fps: f64,
+    mode: SpriteMode,
+    time_source: Arc<TimeSource>,
+
+    playing: bool,
+    // set on the first Update after (re)starting
+    start_time: Option<u64>,
+    // seconds played before the last pause or seek
+    offset: f64,
+    current: usize,
+}
+
+impl Sprite {
+    pub fn new(texture: Texture, columns: u32, rows: u32) -> Box<Self> {
+        let columns = columns.max(1);
+        let rows = rows.max(1);
+        Box::new(Sprite {
+            id: "Sprite".to_string(),
+            senders: Vec::new(),
+            plane: Plane::new(Graphic::Texture(texture.clone())),
+            texture,
+            columns,
+            rows,
+            frames: (columns * rows) as usize,
+            fps: 12.0,
+            mode: SpriteMode::Loop,
+            time_source: Arc::new(PreciseTime),
+            playing: true,
+            start_time: None,
+            offset: 0.0,
+            current: 0,
+        })
+    }
+
+    /// panics unless `fps` is positive.
+    pub fn with_fps(mut self, fps: f64) -> Box<Self> {
+        Sprite::check_fps(fps);
+        self.fps = fps;
+        Box::new(self)
+    }
+
+    pub fn with_mode(mut self, mode: SpriteMode) -> Box<Self> {
+        self.mode = mode;
+        Box::new(self)
+    }
+
+    /// number of used cells, if the last row of the sheet is not full.
+    pub fn with_frames(mut self, frames: usize) -> Box<Self> {
+        self.set_frames(frames);
+        Box::new(self)
+    }
+
+    pub fn with_time_source(mut self, time_source: Arc<TimeSource>) -> Box<Self> {
+        self.time_source = time_source;
+        Box::new(self)
+    }
+
+    pub fn with_playing(mut self, playing: bool) -> Box<Self> {
+        self.playing = playing;
+        Box::new(self)
+    }
+
+    fn check_fps(fps: f64) {
+        assert!(fps.is_finite() && fps > 0.0, "Sprite fps has to be positive, got {}", fps);
+    }
+
+    /// panics unless `fps` is positive.
+    pub fn set_fps(&mut self, fps: f64) {
+        Sprite::check_fps(fps);
+        self.offset = self.elapsed();
+        self.start_time = None;
+        self.fps = fps;
+    }
+
+    pub fn set_mode(&mut self, mode: SpriteMode) {
+        self.mode = mode;
+    }
+
+    pub fn set_frames(&mut self, frames: usize) {
+        let max = (self.columns * self.rows) as usize;
+        self.frames = if frames < 1 { 1 } else if frames > max { max } else { frames };
+    }
+
+    pub fn get_index(&self) -> usize {
+        self.current
+    }
+
+    pub fn get_frames(&self) -> usize {
+        self.frames
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// continues playing, a finished Once sprite starts over.
+    pub fn play(&mut self) {
+        if self.playing { return }
+        if self.mode == SpriteMode::Once && self.current + 1 >= self.frames {
+            self.offset = 0.0;
+            self.current = 0;
+        }
+        self.start_time = None;
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        if !self.playing { return }
+        self.offset = self.elapsed();
+        self.start_time = None;
+        self.playing = false;
+    }
+
+    /// jumps to the frame `index`, keeps playing if it was.
+    pub fn seek(&mut self, index: usize) {
+        self.current = index.min(self.frames - 1);
+        // a bit into the frame, so rounding doesn't land on the one before
+        self.offset = (self.current as f64 + 1e-3) / self.fps;
+        self.start_time = None;
+    }
+
+    // seconds played so far
+    fn elapsed(&self) -> f64 {
+        match self.start_time {
+            Some(start) => self.offset + (self.time_source.now_ns() - start) as f64 * 1e-9,
+            None => self.offset,
+        }
+    }
+
+    fn send(&self, msg: ActionMsgData) {
+        for sender in &self.senders {
+            let _ = sender.send(ActionMsg{
+                sender_id: self.id.clone(),
+                msg: msg.clone(),
+            });
+        }
+    }
+
+    // cell of the current frame in image pixels from the top left
+    fn cut(&self, img_w: u32, img_h: u32) -> Frame<u32> {
+        let (w, h) = (img_w / self.columns, img_h / self.rows);
+        let (x, y) = (self.current as u32 % self.columns, self.current as u32 / self.columns);
+        Frame {
+            p0: Vec2{ x: x * w, y: y * h },
+            p1: Vec2{ x: (x + 1) * w, y: (y + 1) * h },
+        }
+    }
+}
+
+
+impl ActionSendable for Sprite {
+    fn with_id(mut self, id: String) -> Box<Self> {
+        self.id = id;
+        Box::new(self)
+    }
+    fn with_sender(mut self, sender: Sender<ActionMsg>) -> Box<Self> {
+        self.senders.push(sender);
+        Box::new(self)
+    }
+}
+
+
+impl Animateable for Sprite {
+    fn animate_color(&mut self, color: conrod::Color) {
+        self.plane.animate_color(color);
+    }
+    fn animate_alpha(&mut self, alpha: f32) {
+        self.plane.animate_alpha(alpha);
+    }
+
+    fn run(&mut self) {
+        if !self.playing { return }
+
+        if self.start_time.is_none() {
+            self.start_time = Some(self.time_source.now_ns());
+        }
+        let n = (self.elapsed() * self.fps) as usize;
+
+        self.current = match self.mode {
+            SpriteMode::Loop => n % self.frames,
+            SpriteMode::Once => {
+                if n >= self.frames {
+                    self.pause();
+                    self.send(ActionMsgData::Finished);
+                    self.frames - 1
+                } else {
+                    n
+                }
+            },
+        };
+    }
+
+    fn reset(&mut self) {
+        self.plane.reset();
+    }
+}
+
+
+impl Element for Sprite {
+    fn setup(&mut self, ui: &mut conrod::Ui) {
+        self.plane.setup(ui);
+    }
+    fn is_setup(&self) -> bool {
+        self.plane.is_setup()
+    }
+
+    fn set_parent_widget(&mut self, parent: conrod::widget::id::Id) {
+        self.plane.set_parent_widget(parent);
+    }
+    fn set_floating(&mut self, floating: bool) {
+        self.plane.set_floating(floating);
+    }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.plane.set_opacity(opacity);
+    }
+
+    fn stop(&mut self) {
+        self.pause();
+    }
+    fn is_animating(&self) -> bool {
+        self.playing
+    }
+
+    fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
+        if let Some(&(w, h, _)) = ressources.image(&self.texture.get_id()) {
+            let texture = self.texture.clone().with_cut(self.cut(w, h));
+            self.plane.build_texture(ui, ressources, &texture);
+        }
+    }
+
+    fn get_frame(&self) -> Frame<i32> {
+        self.plane.get_frame()
+    }
+    fn set_frame(&mut self, frame: Frame<i32>, window_center: Vec2<i32>) {
+        self.plane.set_frame(frame, window_center);
+    }
+
+    fn set_min_size(&mut self, size: Vec2<i32>) {
+        self.plane.set_min_size(size);
+    }
+    fn get_min_size(&self) -> Vec2<i32> {
+        self.plane.get_min_size()
+    }
+    fn set_max_size(&mut self, size: Vec2<i32>) {
+        self.plane.set_max_size(size);
+    }
+    fn get_max_size(&self) -> Vec2<i32> {
+        self.plane.get_max_size()
+    }
+
+    fn transmit_msg(&mut self, msg: ActionMsg, _stop: bool) {
+        match msg.msg {
+            ActionMsgData::Update => {
+                self.run();
+            },
+            ActionMsgData::Play(ref id) if *id == self.id => self.play(),
+            ActionMsgData::Pause(ref id) if *id == self.id => self.pause(),
+            ActionMsgData::Seek(ref id, index) if *id == self.id => self.seek(index),
+            _ => ()
+        }
+    }
+}
@@ -0,0 +1,496 @@
+use conrod;
+use conrod::color;
+use toml;
+
+use elements::shared::*;
+use elements::theme::*;
+use error::Error;
+
+use std::fs;
+use std::i32;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+
+
+
+
+
+
+
+/*
+.d8888. d888888b db    db db      d88888b
+88'  YP `~~88~~' `8b  d8' 88      88'
+`8bo.      88     `8bd8'  88      88ooooo
+  `Y8b.    88       88    88      88~~~~~
+db   8D    88       88    88booo. 88.
+`8888Y'    YP       YP    Y88888P Y88888P
+
+
+*/
+
+
+
+
+/// Look of an element given by a stylesheet. Unset values fall back to the theme.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Style {
+    pub color: Option<conrod::Color>,
+    pub hover: Option<conrod::Color>,
+    pub click: Option<conrod::Color>,
+    /// image ids, used instead of the colors.
+    pub image: Option<String>,
+    pub image_hover: Option<String>,
+    pub image_click: Option<String>,
+    /// nine-slice borders of the images (left, top, right, bottom).
+    pub slice: Option<(u32, u32, u32, u32)>,
+
+    pub font_id: Option<String>,
+    pub font_size: Option<u32>,
+    pub text: Option<conrod::Color>,
+
+    pub padding: Option<i32>,
+    /// e.g. of the scroll bar.
+    pub width: Option<i32>,
+}
+
+impl Style {
+    pub fn get_graphic(&self) -> Option<Graphic> {
+        self.graphic(&self.image, self.color)
+    }
+
+    pub fn get_graphic_hover(&self) -> Option<Graphic> {
+        self.graphic(&self.image_hover, self.hover)
+    }
+
+    pub fn get_graphic_click(&self) -> Option<Graphic> {
+        self.graphic(&self.image_click, self.click)
+    }
+
+    fn graphic(&self, image: &Option<String>, color: Option<conrod::Color>) -> Option<Graphic> {
+        match *image {
+            Some(ref id) => {
+                let mut texture = Texture::new(id.clone());
+                if let Some((l, t, r, b)) = self.slice {
+                    texture.set_mode(TextureMode::NineSlice(l, t, r, b));
+                }
+                Some(Graphic::Texture(texture))
+            },
+            None => color.map(Graphic::Color),
+        }
+    }
+
+    // values set in `other` win
+    fn merge(&mut self, other: &Style) {
+        macro_rules! take {
+            ($($field:ident),*) => {
+                $( if other.$field.is_some() { self.$field = other.$field.clone(); } )*
+            }
+        }
+        take!(color, hover, click, image, image_hover, image_click, slice,
+            font_id, font_size, text, padding, width);
+    }
+
+    fn parse(table: &toml::value::Table) -> Result<Self, String> {
+        let mut style = Style::default();
+        for (key, value) in table {
+            match key.as_str() {
+                "color" => style.color = Some(parse_color(value)?),
+                "hover" => style.hover = Some(parse_color(value)?),
+                "click" => style.click = Some(parse_color(value)?),
+                "image" => style.image = Some(parse_string(value)?),
+                "image_hover" => style.image_hover = Some(parse_string(value)?),
+                "image_click" => style.image_click = Some(parse_string(value)?),
+                "slice" => {
+                    let s = value.as_array()
+                        .filter(|a| a.len() == 4)
+                        .ok_or(format!("expected [left, top, right, bottom], got {}", value))?;
+                    style.slice = Some((
+                        parse_int(&s[0])? as u32, parse_int(&s[1])? as u32,
+                        parse_int(&s[2])? as u32, parse_int(&s[3])? as u32
+                    ));
+                },
+                "font" => style.font_id = Some(parse_string(value)?),
+                "font_size" => style.font_size = Some(parse_int(value)? as u32),
+                "text" => style.text = Some(parse_color(value)?),
+                "padding" => style.padding = Some(parse_int(value)?),
+                "width" => style.width = Some(parse_int(value)?),
+                _ => return Err(format!("unknown property {}", key)),
+            }
+        }
+        Ok(style)
+    }
+}
+
+
+
+
+/// Which elements a rule applies to, written like `Button`, `#Delete`,
+/// `.warning` or combined `Button.warning#Delete`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    element: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Self, String> {
+        let mut res = Selector { element: None, id: None, classes: Vec::new() };
+
+        // split before every '#' and '.'
+        let mut parts = Vec::new();
+        let mut start = 0;
+        for (i, c) in selector.char_indices() {
+            if (c == '#' || c == '.') && i > start {
+                parts.push(&selector[start..i]);
+                start = i;
+            }
+        }
+        parts.push(&selector[start..]);
+
+        for part in parts {
+            if part.starts_with('#') {
+                res.id = Some(part[1..].to_string());
+            } else if part.starts_with('.') {
+                res.classes.push(part[1..].to_string());
+            } else {
+                res.element = Some(part.to_string());
+            }
+        }
+
+        let empty = res.element.as_ref().map_or(false, |e| e.is_empty())
+            || res.id.as_ref().map_or(false, |id| id.is_empty())
+            || res.classes.iter().any(|c| c.is_empty());
+        if empty || selector.is_empty() {
+            return Err(format!("invalid selector {:?}", selector));
+        }
+        Ok(res)
+    }
+
+    pub fn matches(&self, element: &str, id: &str, classes: &[String]) -> bool {
+        self.element.as_ref().map_or(true, |e| e == element)
+            && self.id.as_ref().map_or(true, |i| i == id)
+            && self.classes.iter().all(|c| classes.contains(c))
+    }
+
+    // ids beat classes beat element types
+    fn specificity(&self) -> usize {
+        let id = if self.id.is_some() { 100 } else { 0 };
+        let element = if self.element.is_some() { 1 } else { 0 };
+        id + 10 * self.classes.len() + element
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleRule {
+    pub selector: Selector,
+    pub style: Style,
+}
+
+
+/// Merges the styles of all matching rules, more specific selectors win. TOML
+/// tables aren't ordered, so equally specific rules are applied alphabetically.
+pub fn find_style(rules: &[StyleRule], element: &str, id: &str, classes: &[String]) -> Style {
+    let mut matching: Vec<&StyleRule> = rules.iter()
+        .filter(|r| r.selector.matches(element, id, classes))
+        .collect();
+    // stable, keeps the order of the rules
+    matching.sort_by_key(|r| r.selector.specificity());
+
+    let mut style = Style::default();
+    for rule in matching {
+        style.merge(&rule.style);
+    }
+    style
+}
+
+
+
+
+/// Styles loaded from a TOML file, e.g.
+/// ```toml
+/// [theme]
+/// base = "dark"
+/// scroll_bar_width = 10
+///
+/// [Button]
+/// color = "#404040"
+/// padding = 8
+///
+/// ["Button#Delete"]
+/// color = "red"
+/// hover = "light_red"
+/// ```
+/// The optional `[theme]` table overwrites the named fields of the window's
+/// theme, `base` ("classic", "light" or "dark") starts from a built-in theme instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stylesheet {
+    // checked when parsed, applied to the window's theme
+    theme: Option<toml::value::Table>,
+    rules: Vec<StyleRule>,
+
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+}
+
+impl Stylesheet {
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        Self::parse_toml(text).map_err(Error::InvalidData)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        if !path.exists() {
+            return Err(Error::MissingFile(path.to_path_buf()));
+        }
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        let text = fs::read_to_string(path)
+            .map_err(|e| Error::io(path, e))?;
+        let mut sheet = Self::parse_toml(&text)
+            .map_err(|e| Error::Decode(path.to_path_buf(), e))?;
+        sheet.path = Some(path.to_path_buf());
+        sheet.modified = modified;
+        Ok(sheet)
+    }
+
+    /// true if the file was written since it was loaded.
+    pub fn is_modified(&self) -> bool {
+        match self.path {
+            Some(ref path) => {
+                let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+                modified.is_some() && modified != self.modified
+            },
+            None => false,
+        }
+    }
+
+    /// loads the file again, keeps the old styles if that fails.
+    pub fn reload(&mut self) -> Result<(), Error> {
+        let path = match self.path {
+            Some(ref path) => path.clone(),
+            None => return Ok(()),
+        };
+        // don't try again before the next change
+        self.modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        *self = Self::load(&path)?;
+        Ok(())
+    }
+
+    /// `theme` with the fields of the `[theme]` table overwritten and the rules
+    /// added after its own, so they win over equally specific ones.
+    pub fn apply(&self, theme: &Theme) -> Theme {
+        let mut res = match self.theme {
+            Some(ref table) => parse_theme(table, theme.clone()).unwrap_or(theme.clone()),
+            None => theme.clone(),
+        };
+        // a base theme doesn't drop the rules set in code
+        res.rules = theme.rules.clone();
+        res.rules.extend(self.rules.iter().cloned());
+        res
+    }
+
+    fn parse_toml(text: &str) -> Result<Self, String> {
+        let value = text.parse::<toml::Value>().map_err(|e| format!("{}", e))?;
+        let table = value.as_table().ok_or("expected a table".to_string())?;
+
+        let mut sheet = Stylesheet {
+            theme: None,
+            rules: Vec::new(),
+            path: None,
+            modified: None,
+        };
+        for (key, value) in table {
+            let entries = value.as_table()
+                .ok_or(format!("{} has to be a table", key))?;
+            if key == "theme" {
+                parse_theme(entries, Theme::default()).map_err(|e| format!("[theme]: {}", e))?;
+                sheet.theme = Some(entries.clone());
+            } else {
+                sheet.rules.push(StyleRule {
+                    selector: Selector::parse(key)?,
+                    style: Style::parse(entries).map_err(|e| format!("[{}]: {}", key, e))?,
+                });
+            }
+        }
+        Ok(sheet)
+    }
+}
+
+
+
+
+// overwrites the fields of `theme` given in the table
+fn parse_theme(table: &toml::value::Table, theme: Theme) -> Result<Theme, String> {
+    let mut theme = match table.get("base").map(parse_string) {
+        Some(Ok(ref base)) if base == "dark" => Theme::dark(),
+        Some(Ok(ref base)) if base == "light" => Theme::light(),
+        Some(Ok(ref base)) if base == "classic" => Theme::classic(),
+        Some(Ok(base)) => return Err(format!("unknown base theme {}", base)),
+        Some(Err(e)) => return Err(e),
+        None => theme,
+    };
+    for (key, value) in table {
+        match key.as_str() {
+            "base" => (),
+            "background" => theme.background = parse_color(value)?,
+            "font" => theme.font_id = parse_string(value)?,
+            "font_size" => theme.font_size = parse_int(value)? as u32,
+            "text" => theme.text = parse_color(value)?,
+            "button" => theme.button = parse_color(value)?,
+            "button_hover" => theme.button_hover = parse_color(value)?,
+            "button_click" => theme.button_click = parse_color(value)?,
+            "padding" => theme.padding = parse_int(value)?,
+            "scroll_bar" => theme.scroll_bar = parse_color(value)?,
+            "scroll_bar_width" => theme.scroll_bar_width = parse_int(value)?,
+            _ => return Err(format!("unknown property {}", key)),
+        }
+    }
+    Ok(theme)
+}
+
+fn parse_string(value: &toml::Value) -> Result<String, String> {
+    value.as_str()
+        .map(|s| s.to_string())
+        .ok_or(format!("expected a string, got {}", value))
+}
+
+// sizes in pixel, larger values would wrap when converted
+fn parse_int(value: &toml::Value) -> Result<i32, String> {
+    value.as_integer()
+        .filter(|i| *i >= 0 && *i <= i64::from(i32::MAX))
+        .map(|i| i as i32)
+        .ok_or(format!("expected a positive integer up to {}, got {}", i32::MAX, value))
+}
+
+/// `"#rrggbb"`, `"#rrggbbaa"` or a conrod color name like `"light_blue"`.
+fn parse_color(value: &toml::Value) -> Result<conrod::Color, String> {
+    let s = parse_string(value)?;
+    if s.starts_with('#') {
+        let hex = &s[1..];
+        let byte = |i: usize| u8::from_str_radix(&hex[i..i+2], 16)
+            .map_err(|_| format!("invalid color {}", s));
+        if !hex.is_ascii() {
+            return Err(format!("invalid color {}", s));
+        }
+        return match hex.len() {
+            6 => Ok(color::rgb_bytes(byte(0)?, byte(2)?, byte(4)?)),
+            8 => Ok(color::rgba_bytes(byte(0)?, byte(2)?, byte(4)?, byte(6)? as f32 / 255.0)),
+            _ => Err(format!("invalid color {}", s)),
+        };
+    }
+
+    let c = match s.to_lowercase().replace(' ', "_").as_str() {
+        "light_red" => color::LIGHT_RED,
+        "red" => color::RED,
+        "dark_red" => color::DARK_RED,
+        "light_orange" => color::LIGHT_ORANGE,
+        "orange" => color::ORANGE,
+        "dark_orange" => color::DARK_ORANGE,
+        "light_yellow" => color::LIGHT_YELLOW,
+        "yellow" => color::YELLOW,
+        "dark_yellow" => color::DARK_YELLOW,
+        "light_green" => color::LIGHT_GREEN,
+        "green" => color::GREEN,
+        "dark_green" => color::DARK_GREEN,
+        "light_blue" => color::LIGHT_BLUE,
+        "blue" => color::BLUE,
+        "dark_blue" => color::DARK_BLUE,
+        "light_purple" => color::LIGHT_PURPLE,
+        "purple" => color::PURPLE,
+        "dark_purple" => color::DARK_PURPLE,
+        "light_brown" => color::LIGHT_BROWN,
+        "brown" => color::BROWN,
+        "dark_brown" => color::DARK_BROWN,
+        "light_grey" | "light_gray" => color::LIGHT_GREY,
+        "grey" | "gray" => color::GREY,
+        "dark_grey" | "dark_gray" => color::DARK_GREY,
+        "light_charcoal" => color::LIGHT_CHARCOAL,
+        "charcoal" => color::CHARCOAL,
+        "dark_charcoal" => color::DARK_CHARCOAL,
+        "black" => color::BLACK,
+        "white" => color::WHITE,
+        "transparent" => color::TRANSPARENT,
+        _ => return Err(format!("unknown color {}", s)),
+    };
+    Ok(c)
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(selector: &str, padding: i32) -> StyleRule {
+        StyleRule {
+            selector: Selector::parse(selector).unwrap(),
+            style: Style { padding: Some(padding), ..Style::default() },
+        }
+    }
+
+    #[test]
+    fn selectors_match_type_id_and_classes() {
+        let selector = Selector::parse("Button.warning.big#Delete").unwrap();
+        let classes = vec!["warning".to_string(), "big".to_string()];
+        assert!(selector.matches("Button", "Delete", &classes));
+        assert!(!selector.matches("Text", "Delete", &classes));
+        assert!(!selector.matches("Button", "Other", &classes));
+        assert!(!selector.matches("Button", "Delete", &classes[..1]));
+
+        assert!(Selector::parse("").is_err());
+        assert!(Selector::parse("Button#").is_err());
+        assert!(Selector::parse("..warning").is_err());
+    }
+
+    #[test]
+    fn more_specific_rules_win() {
+        let rules = vec![rule("#Delete", 3), rule(".warning", 2), rule("Button", 1)];
+        let classes = vec!["warning".to_string()];
+        assert_eq!(find_style(&rules, "Button", "Delete", &classes).padding, Some(3));
+        assert_eq!(find_style(&rules, "Button", "Other", &classes).padding, Some(2));
+        assert_eq!(find_style(&rules, "Button", "Other", &[]).padding, Some(1));
+        assert_eq!(find_style(&rules, "Text", "Other", &[]).padding, None);
+    }
+
+    #[test]
+    fn colors_are_parsed() {
+        let color = |s: &str| parse_color(&toml::Value::String(s.to_string()));
+        assert_eq!(color("#ff0000").unwrap(), color::rgb_bytes(255, 0, 0));
+        assert_eq!(color("#00ff0080").unwrap(), color::rgba_bytes(0, 255, 0, 128.0 / 255.0));
+        assert_eq!(color("Light Blue").unwrap(), color::LIGHT_BLUE);
+        assert!(color("#ff00").is_err());
+        assert!(color("#gg0000").is_err());
+        assert!(color("#ffäf").is_err());
+        assert!(color("sky").is_err());
+    }
+
+    #[test]
+    fn invalid_sheets_are_rejected() {
+        assert!(Stylesheet::parse("[Button]\npadding = -1").is_err());
+        assert!(Stylesheet::parse("[Button]\npadding = 3000000000").is_err());
+        assert!(Stylesheet::parse("[theme]\nfont_size = 3000000000").is_err());
+        assert!(Stylesheet::parse("[Button]\nsize = 3").is_err());
+        assert!(Stylesheet::parse("[Button]\nslice = [1, 2, 3]").is_err());
+        assert!(Stylesheet::parse("[theme]\nbase = \"pink\"").is_err());
+        assert!(Stylesheet::parse("padding = 3").is_err());
+        assert!(Stylesheet::parse("[Button").is_err());
+    }
+
+    #[test]
+    fn sheet_is_layered_on_the_theme() {
+        let sheet = Stylesheet::parse("[theme]\npadding = 7\n\n[Button]\nwidth = 4").unwrap();
+        let mut theme = Theme::dark();
+        theme.rules.push(rule("Button", 1));
+
+        let applied = sheet.apply(&theme);
+        assert_eq!(applied.padding, 7);
+        assert_eq!(applied.background, theme.background);
+        let style = applied.style("Button", "", &[]);
+        assert_eq!(style.padding, Some(1));
+        assert_eq!(style.width, Some(4));
+
+        let sheet = Stylesheet::parse("[theme]\nbase = \"light\"").unwrap();
+        assert_eq!(sheet.apply(&theme).background, Theme::light().background);
+    }
+}
@@ -0,0 +1,111 @@
+use conrod;
+use conrod::color;
+
+use elements::shared::*;
+use elements::style::*;
+
+
+
+
+
+
+
+
+/*
+d888888b db   db d88888b .88b  d88. d88888b
+`~~88~~' 88   88 88'     88'YbdP`88 88'
+   88    88ooo88 88ooooo 88  88  88 88ooooo
+   88    88~~~88 88~~~~~ 88  88  88 88~~~~~
+   88    88   88 88.     88  88  88 88.
+   YP    YP   YP Y88888P YP  YP  YP Y88888P
+
+
+*/
+
+
+
+
+/// Default look of all elements which weren't styled explicitly. Set it with
+/// `Window::set_theme` or switch it while running by sending
+/// `ActionMsgData::Theme` to the window. Images, sprites, animated images and
+/// the shapes of canvases and plots keep their own colors, only their themed
+/// fonts follow the theme.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// cleared behind everything.
+    pub background: conrod::Color,
+
+    /// used by fonts created with `Font::themed`.
+    pub font_id: String,
+    pub font_size: u32,
+    pub text: conrod::Color,
+
+    pub button: conrod::Color,
+    pub button_hover: conrod::Color,
+    pub button_click: conrod::Color,
+    /// space between a button and its label.
+    pub padding: i32,
+
+    pub scroll_bar: conrod::Color,
+    pub scroll_bar_width: i32,
+
+    /// styles for single element types, ids and classes, see `Stylesheet`.
+    pub rules: Vec<StyleRule>,
+}
+
+impl Theme {
+    /// the look from before themes: black background and labels filling their buttons.
+    pub fn classic() -> Self {
+        Theme {
+            background: color::BLACK,
+            padding: 0,
+            ..Theme::light()
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            background: color::WHITE,
+            font_id: "NotoSans-Regular".to_string(),
+            font_size: 42,
+            text: color::BLACK,
+            button: color::LIGHT_GREY,
+            button_hover: color::LIGHT_YELLOW,
+            button_click: color::LIGHT_GREEN,
+            padding: 4,
+            scroll_bar: color::LIGHT_BLUE,
+            scroll_bar_width: 15,
+            rules: Vec::new(),
+        }
+    }
+
+    pub fn dark() -> Self {
+        Theme {
+            background: color::DARK_CHARCOAL,
+            text: color::WHITE,
+            button: color::CHARCOAL,
+            button_hover: color::LIGHT_CHARCOAL,
+            button_click: color::DARK_BLUE,
+            scroll_bar: color::BLUE,
+            ..Theme::light()
+        }
+    }
+
+    /// the themed font, follows later theme changes.
+    pub fn font(&self) -> Font {
+        let mut font = Font::themed();
+        font.apply_theme(self);
+        font
+    }
+
+    /// the style given by the rules for an element of type `element`, e.g. "Button".
+    pub fn style(&self, element: &str, id: &str, classes: &[String]) -> Style {
+        find_style(&self.rules, element, id, classes)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::classic()
+    }
+}
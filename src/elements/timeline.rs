@@ -0,0 +1,595 @@
+use conrod;
+
+use elements::{*, action::*, theme::*, easing::*};
+use std::sync::{Arc, RwLock};
+use std::i32;
+
+
+
+
+
+
+
+
+
+/*
+db   dD d88888b db    db d88888b d8888b.  .d8b.  .88b  d88. d88888b .d8888.
+88 ,8P' 88'     `8b  d8' 88'     88  `8D d8' `8b 88'YbdP`88 88'     88'  YP
+88,8P   88ooooo  `8bd8'  88ooo   88oobY' 88ooo88 88  88  88 88ooooo `8bo.
+88`8b   88~~~~~    88    88~~~   88`8b   88~~~88 88  88  88 88~~~~~   `Y8b.
+88 `88. 88.        88    88      88 `88. 88   88 88  88  88 88.     db   8D
+YP   YD Y88888P    YP    YP      88   YD YP   YP YP  YP  YP Y88888P `8888Y'
+
+
+*/
+
+
+
+
+/// Values at given times in ms. The easing of a keyframe is used for the
+/// segment leading up to it. Can be used as SizeAnimation and PositionAnimation,
+/// the times are absolute, so the duration of the animation is ignored.
+#[derive(Debug, Clone)]
+pub struct Keyframes {
+    frames: Vec<(f64, (Dim, Dim), Easing)>,
+}
+
+impl Keyframes {
+    /// starts with `value` at time 0.
+    pub fn new(value: (Dim, Dim)) -> Self {
+        Keyframes {
+            frames: vec![(0.0, value, Easing::Linear)],
+        }
+    }
+
+    /// adds a keyframe at `t_ms`, keyframes added out of order are sorted in.
+    /// Negative or non-finite times are ignored.
+    pub fn key(mut self, t_ms: f64, value: (Dim, Dim), easing: Easing) -> Self {
+        if !t_ms.is_finite() || t_ms < 0.0 {
+            println!("Keyframes: ignoring keyframe at {} ms", t_ms);
+            return self;
+        }
+        // after all keyframes at the same time
+        let index = self.frames.iter().position(|f| f.0 > t_ms).unwrap_or(self.frames.len());
+        self.frames.insert(index, (t_ms, value, easing));
+        self
+    }
+
+    /// time of the last keyframe.
+    pub fn duration(&self) -> f64 {
+        match self.frames.last() {
+            Some(&(t, _, _)) => t,
+            None => 0.0,
+        }
+    }
+
+    pub fn value(&self, t: f64) -> (Dim, Dim) {
+        let first = self.frames[0];
+        if t <= first.0 { return first.1 }
+
+        for w in self.frames.windows(2) {
+            let (t0, v0, _) = w[0];
+            let (t1, v1, easing) = w[1];
+            if t < t1 {
+                let s = if t1 > t0 { easing.ease((t - t0) / (t1 - t0)) } else { 1.0 };
+                return (lerp_dim(v0.0, v1.0, s), lerp_dim(v0.1, v1.1, s));
+            }
+        }
+        self.frames[self.frames.len() - 1].1
+    }
+}
+
+impl SizeAnimation for Keyframes {
+    fn calc(&self, t: f64, _duration: f64) -> (Dim, Dim) {
+        self.value(t)
+    }
+}
+
+impl PositionAnimation for Keyframes {
+    fn calc(&self, t: f64, _duration: f64) -> (Dim, Dim) {
+        self.value(t)
+    }
+}
+
+
+
+
+
+
+
+
+
+
+/*
+ .o88b. db      d888888b d8888b.
+d8P  Y8 88        `88'   88  `8D
+8P      88         88    88oodD'
+8b      88         88    88~~~
+Y8b  d8 88booo.   .88.   88
+ `Y88P' Y88888P Y888888P 88
+
+
+*/
+
+
+
+
+/// Values of all tracks of a Clip at one point in time, None if not animated.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ClipState {
+    pub size: Option<(Dim, Dim)>,
+    pub position: Option<(Dim, Dim)>,
+    pub color: Option<conrod::Color>,
+    pub alpha: Option<f32>,
+}
+
+
+
+/// Building blocks of a Timeline. All durations are in ms.
+/// If several clips animate the same property at once, the later one wins.
+/// A finished clip holds its last value until the whole Timeline is done.
+pub enum Clip {
+    Size(f64, Box<SizeAnimation>),
+    Position(f64, Box<PositionAnimation>),
+    Color(f64, Box<ColorAnimation>),
+    Alpha(f64, Box<AlphaAnimation>),
+    Delay(f64),
+    /// one after the other
+    Sequence(Vec<Clip>),
+    /// all at once
+    Parallel(Vec<Clip>),
+}
+
+impl Clip {
+    pub fn size_keyframes(keyframes: Keyframes) -> Self {
+        Clip::Size(keyframes.duration(), Box::new(keyframes))
+    }
+
+    pub fn position_keyframes(keyframes: Keyframes) -> Self {
+        Clip::Position(keyframes.duration(), Box::new(keyframes))
+    }
+
+    /// runs all clips in parallel, each one `step_ms` later than the one before.
+    pub fn stagger(clips: Vec<Clip>, step_ms: f64) -> Self {
+        Clip::Parallel(
+            clips.into_iter().enumerate().map(|(i, clip)| {
+                Clip::Sequence(vec![Clip::Delay(i as f64 * step_ms), clip])
+            }).collect()
+        )
+    }
+
+    pub fn duration(&self) -> f64 {
+        match *self {
+            Clip::Size(d, _) | Clip::Position(d, _)
+            | Clip::Color(d, _) | Clip::Alpha(d, _) | Clip::Delay(d) => d,
+            Clip::Sequence(ref clips) => clips.iter().map(|c| c.duration()).sum(),
+            Clip::Parallel(ref clips) => clips.iter()
+                .map(|c| c.duration())
+                .fold(0.0, |a, b| if a > b { a } else { b }),
+        }
+    }
+
+    /// evaluates the clip at time `t` and stores the resulting values.
+    pub fn eval(&self, t: f64, state: &mut ClipState) {
+        if t < 0.0 { return }
+        match *self {
+            Clip::Size(d, ref f) => {
+                state.size = Some(f.calc(if t < d { t } else { d }, d));
+            },
+            Clip::Position(d, ref f) => {
+                state.position = Some(f.calc(if t < d { t } else { d }, d));
+            },
+            Clip::Color(d, ref f) => {
+                state.color = Some(f.calc(if t < d { t } else { d }, d));
+            },
+            Clip::Alpha(d, ref f) => {
+                state.alpha = Some(f.calc(if t < d { t } else { d }, d));
+            },
+            Clip::Delay(_) => (),
+            Clip::Sequence(ref clips) => {
+                let mut offset = 0.0;
+                for clip in clips {
+                    if t < offset { break }
+                    clip.eval(t - offset, state);
+                    offset += clip.duration();
+                }
+            },
+            Clip::Parallel(ref clips) => {
+                for clip in clips {
+                    clip.eval(t, state);
+                }
+            },
+        }
+    }
+}
+
+
+
+
+
+
+
+
+
+
+/*
+d888888b d888888b .88b  d88. d88888b db      d888888b d8b   db d88888b
+`~~88~~'   `88'   88'YbdP`88 88'     88        `88'   888o  88 88'
+   88       88    88  88  88 88ooooo 88         88    88V8o 88 88ooooo
+   88       88    88  88  88 88~~~~~ 88         88    88 V8o88 88~~~~~
+   88      .88.   88  88  88 88.     88booo.   .88.   88  V888 88.
+   YP    Y888888P YP  YP  YP Y88888P Y88888P Y888888P VP   V8P Y88888P
+
+
+*/
+
+
+
+
+/// Clock shared between Timelines. Starting it starts all of them, starting a
+/// single Timeline on a running clock only restarts that one.
+#[derive(Clone)]
+pub struct TimelineClock {
+    start: Arc<RwLock<Option<u64>>>,
+    time_source: Arc<TimeSource>,
+}
+
+impl TimelineClock {
+    pub fn new() -> Self {
+        TimelineClock::with_time_source(Arc::new(PreciseTime))
+    }
+
+    pub fn with_time_source(time_source: Arc<TimeSource>) -> Self {
+        TimelineClock {
+            start: Arc::new(RwLock::new(None)),
+            time_source,
+        }
+    }
+
+    /// (re)starts the clock.
+    pub fn start(&self) {
+        *self.start.write().unwrap() = Some(self.time_source.now_ns());
+    }
+
+    pub fn stop(&self) {
+        *self.start.write().unwrap() = None;
+    }
+
+    /// time in ms since the start, None if not running.
+    pub fn time(&self) -> Option<f64> {
+        match *self.start.read().unwrap() {
+            Some(t0) => Some(self.time_source.now_ns().saturating_sub(t0) as f64 * 1e-6),
+            None => None,
+        }
+    }
+
+    /// time of the last start in ns of the time source, None if not running.
+    pub fn get_start_ns(&self) -> Option<u64> {
+        *self.start.read().unwrap()
+    }
+
+    pub fn now_ns(&self) -> u64 {
+        self.time_source.now_ns()
+    }
+}
+
+
+
+
+widget_ids!(
+    #[derive(Clone)]
+    struct TimelineIds {
+        timeline,
+    }
+);
+
+
+/// Animates an element along a Clip. Unlike nested Animations, all
+/// tracks of one Timeline are applied together, so their order
+/// doesn't matter and the element is only reset once everything is done.
+pub struct Timeline {
+    ids: Option<TimelineIds>,
+    parent: Option<conrod::widget::id::Id>,
+    floating: bool,
+
+    pub element: Box<Animateable>,
+
+    clip: Clip,
+    clock: TimelineClock,
+    // set when only this timeline was (re)started on a running clock
+    started: Option<u64>,
+    delay: f64,
+    active: bool,
+
+    min_size: Vec2<i32>,
+    max_size: Vec2<i32>,
+}
+
+impl Timeline {
+    pub fn new(element: Box<Animateable>, clip: Clip) -> Box<Self> {
+        Box::new(Timeline {
+            ids: None,
+            parent: None,
+            floating: false,
+            element,
+            clip,
+            clock: TimelineClock::new(),
+            started: None,
+            delay: 0.0,
+            active: false,
+            min_size: Vec2::zero(),
+            max_size: Vec2 {x: i32::MAX, y: i32::MAX},
+        })
+    }
+
+    /// drive this Timeline by a shared clock, e.g. to start several at once.
+    pub fn with_clock(mut self, clock: TimelineClock) -> Box<Self> {
+        self.clock = clock;
+        Box::new(self)
+    }
+
+    /// start the clip `delay_ms` after the clock. Used for staggering.
+    pub fn with_delay(mut self, delay_ms: f64) -> Box<Self> {
+        self.delay = delay_ms;
+        Box::new(self)
+    }
+
+    pub fn with_floating(mut self, floating: bool) -> Box<Self> {
+        self.floating = floating;
+        Box::new(self)
+    }
+
+    pub fn get_clock(&self) -> TimelineClock {
+        self.clock.clone()
+    }
+
+    pub fn set_clip(&mut self, clip: Clip) {
+        self.clip = clip;
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.delay + self.clip.duration()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.active
+    }
+
+    // ms since this timeline was started, either with the clock or on its own
+    fn time(&self) -> Option<f64> {
+        let clock = self.clock.get_start_ns()?;
+        let t0 = match self.started {
+            Some(t) if t > clock => t,
+            _ => clock,
+        };
+        let now = self.clock.now_ns();
+        Some(if now > t0 { (now - t0) as f64 * 1e-6 } else { 0.0 })
+    }
+}
+
+
+impl Animateable for Timeline {
+    fn animate_size(&mut self, xy: (Dim,Dim)) {
+        self.element.animate_size(xy);
+    }
+    fn animate_position(&mut self, xy: (Dim,Dim)) {
+        self.element.animate_position(xy);
+    }
+    fn animate_color(&mut self, color: conrod::Color) {
+        self.element.animate_color(color);
+    }
+    fn animate_alpha(&mut self, alpha: f32) {
+        self.element.animate_alpha(alpha);
+    }
+
+    /// starts the clock if it isn't running, otherwise only this timeline
+    /// starts over, the others on the same clock keep going.
+    fn start(&mut self) {
+        if self.active { return }
+        if self.clock.get_start_ns().is_none() {
+            self.started = None;
+            self.clock.start();
+        } else {
+            self.started = Some(self.clock.now_ns());
+        }
+    }
+
+    fn run(&mut self) {
+        let t = match self.time() {
+            Some(t) if t < self.duration() => t - self.delay,
+            _ => {
+                if self.active { self.reset() }
+                return
+            }
+        };
+        if t < 0.0 { return }
+
+        if !self.active {
+            self.active = true;
+            // TODO not ideal. need to manage individual floating property
+            self.element.set_floating(self.floating);
+            self.element.start();
+        }
+
+        let mut state = ClipState::default();
+        self.clip.eval(t, &mut state);
+
+        if let Some(xy) = state.size {
+            self.element.animate_size(xy);
+        }
+        if let Some(xy) = state.position {
+            self.element.animate_position(xy);
+        }
+        if let Some(color) = state.color {
+            self.element.animate_color(color);
+        }
+        if let Some(alpha) = state.alpha {
+            self.element.animate_alpha(alpha);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.active = false;
+        self.started = None;
+        // TODO not ideal. need to manage individual floating property
+        self.element.set_floating(false);
+        self.element.reset();
+    }
+}
+
+
+impl Element for Timeline {
+    fn setup(&mut self, ui: &mut conrod::Ui) {
+        self.ids = Some(TimelineIds::new(ui.widget_id_generator()));
+        self.element.setup(ui);
+    }
+    fn is_setup(&self) -> bool {
+        self.element.is_setup()
+    }
+
+    fn set_floating(&mut self, floating: bool) {
+        self.element.set_floating(floating);
+    }
+    fn set_opacity(&mut self, opacity: f32) {
+        self.element.set_opacity(opacity);
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        self.element.set_theme(theme);
+    }
+
+    fn set_parent_widget(&mut self, parent: conrod::widget::id::Id) {
+        self.parent = Some(parent);
+        self.element.set_parent_widget(parent);
+    }
+
+    fn stop(&mut self) {
+        self.element.stop();
+        self.active = false;
+        self.started = None;
+    }
+    fn is_animating(&self) -> bool {
+        let running = match self.time() {
+            Some(t) => t < self.duration(),
+            None => false,
+        };
+        running || self.active || self.element.is_animating()
+    }
+    fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
+        self.element.build_window(ui, ressources);
+    }
+
+    fn get_frame(&self) -> Frame<i32> {
+        self.element.get_frame()
+    }
+    fn set_frame(&mut self, frame: Frame<i32>, window_center: Vec2<i32>) {
+        self.element.set_frame(frame, window_center);
+    }
+
+    fn set_max_size(&mut self, size: Vec2<i32>) {
+        self.max_size = size;
+    }
+    fn set_min_size(&mut self, size: Vec2<i32>) {
+        self.min_size = size;
+    }
+
+    fn get_min_size(&self) -> Vec2<i32> {
+        self.element.get_min_size()
+    }
+    fn get_max_size(&self) -> Vec2<i32> {
+        self.element.get_max_size()
+    }
+    fn transmit_msg(&mut self, msg: ActionMsg, stop: bool) {
+        match msg.msg {
+            ActionMsgData::Update => {
+                self.run();
+            },
+            _ => ()
+        }
+
+        if !stop {
+            self.element.transmit_msg(msg, false);
+        }
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use elements::action::tests::Probe;
+
+    fn clip() -> Clip {
+        Clip::size_keyframes(
+            Keyframes::new((Dim::Absolute(0), Dim::Absolute(0)))
+                .key(100.0, (Dim::Absolute(100), Dim::Absolute(100)), Easing::Linear)
+        )
+    }
+
+    #[test]
+    fn clock_follows_the_time_source() {
+        let time = ManualTime::new();
+        let clock = TimelineClock::with_time_source(Arc::new(time.clone()));
+        assert_eq!(clock.time(), None);
+
+        time.advance_ms(10.0);
+        clock.start();
+        time.advance_ms(40.0);
+        assert!((clock.time().unwrap() - 40.0).abs() < 1e-9);
+
+        clock.stop();
+        assert_eq!(clock.time(), None);
+    }
+
+    #[test]
+    fn clock_stays_at_zero_when_the_time_goes_back() {
+        let time = ManualTime::new();
+        let clock = TimelineClock::with_time_source(Arc::new(time.clone()));
+        time.set_ms(100.0);
+        clock.start();
+        time.set_ms(20.0);
+        assert_eq!(clock.time(), Some(0.0));
+    }
+
+    #[test]
+    fn restarting_one_timeline_keeps_the_shared_clock() {
+        let time = ManualTime::new();
+        let clock = TimelineClock::with_time_source(Arc::new(time.clone()));
+        let (probe_a, log_a) = Probe::new();
+        let (probe_b, log_b) = Probe::new();
+        let mut a = Timeline::new(probe_a, clip()).with_clock(clock.clone());
+        let mut b = Timeline::new(probe_b, clip()).with_clock(clock.clone());
+
+        a.start();
+        time.advance_ms(50.0);
+        a.run();
+        assert_eq!(log_a.borrow().size, Some((50, 50)));
+
+        b.start();
+        time.advance_ms(25.0);
+        a.run();
+        b.run();
+        assert_eq!(log_a.borrow().size, Some((75, 75)));
+        assert_eq!(log_b.borrow().size, Some((25, 25)));
+
+        time.advance_ms(50.0);
+        a.run();
+        assert!(!a.is_running());
+        assert_eq!(log_a.borrow().resets, 1);
+        assert!(b.is_animating());
+    }
+
+    #[test]
+    fn keys_are_sorted_in() {
+        let keyframes = Keyframes::new((Dim::Absolute(0), Dim::Absolute(0)))
+            .key(200.0, (Dim::Absolute(200), Dim::Absolute(0)), Easing::Linear)
+            .key(100.0, (Dim::Absolute(100), Dim::Absolute(0)), Easing::Linear)
+            .key(-1.0, (Dim::Absolute(-1), Dim::Absolute(0)), Easing::Linear);
+        assert_eq!(keyframes.duration(), 200.0);
+        match keyframes.value(150.0).0 {
+            Dim::Absolute(x) => assert_eq!(x, 150),
+            _ => panic!(),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use conrod;
+
+use elements::{*, action::*, easing::*};
+use std::sync::Arc;
+
+
+
+
+
+
+
+
+/*
+d888888b d8888b.  .d8b.  d8b   db .d8888. d888888b d888888b d888888b  .d88b.  d8b   db
+`~~88~~' 88  `8D d8' `8b 888o  88 88'  YP   `88'   `~~88~~'   `88'   .8P  Y8. 888o  88
+   88    88oobY' 88ooo88 88V8o 88 `8bo.      88       88       88    88    88 88V8o 88
+   88    88`8b   88~~~88 88 V8o88   `Y8b.    88       88       88    88    88 88 V8o88
+   88    88 `88. 88   88 88  V888 db   8D   .88.      88      .88.   `8b  d8' 88  V888
+   YP    88   YD YP   YP VP   V8P `8888Y' Y888888P    YP    Y888888P  `Y88P'  VP   V8P
+
+
+*/
+
+
+
+
+pub fn lerp_frame(from: Frame<i32>, to: Frame<i32>, s: f64) -> Frame<i32> {
+    let l = |a: i32, b: i32| (a as f64 + (b - a) as f64 * s).round() as i32;
+    Frame {
+        p0: Vec2{ x: l(from.p0.x, to.p0.x), y: l(from.p0.y, to.p0.y) },
+        p1: Vec2{ x: l(from.p1.x, to.p1.x), y: l(from.p1.y, to.p1.y) },
+    }
+}
+
+// zero sized frame in the center of `frame`
+fn collapsed(frame: Frame<i32>) -> Frame<i32> {
+    let c = frame.center();
+    Frame { p0: c, p1: c }
+}
+
+// overshooting easings leave [0,1]
+fn clamp_opacity(opacity: f32) -> f32 {
+    opacity.max(0.0).min(1.0)
+}
+
+
+
+/// Animated layout changes of containers. Elements slide from their old to their
+/// new frame, new elements grow and fade in, dismissed ones shrink and fade out
+/// and are dropped afterwards. Only structural changes are animated, resizing the
+/// window is not.
+pub struct LayoutTransition {
+    duration: f64,
+    easing: Easing,
+    time_source: Arc<TimeSource>,
+
+    start_time: u64,
+    running: bool,
+
+    // per element, None for new elements
+    from: Vec<Option<Frame<i32>>>,
+    to: Vec<Frame<i32>>,
+    // per element, opacity at the start, below 1 while fading in
+    fade_from: Vec<f32>,
+    // with frame and opacity at the start
+    leaving: Vec<(Box<Element>, Frame<i32>, f32)>,
+}
+
+impl LayoutTransition {
+    pub fn new(duration_ms: f64, easing: Easing) -> Self {
+        LayoutTransition {
+            duration: duration_ms,
+            easing,
+            time_source: Arc::new(PreciseTime),
+            start_time: 0,
+            running: false,
+            from: Vec::new(),
+            to: Vec::new(),
+            fade_from: Vec::new(),
+            leaving: Vec::new(),
+        }
+    }
+
+    pub fn with_time_source(mut self, time_source: Arc<TimeSource>) -> Self {
+        self.time_source = time_source;
+        self
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// current frames of the elements, None for the one at `inserted`.
+    pub fn current_frames(elements: &[Box<Element>], inserted: Option<usize>) -> Vec<Option<Frame<i32>>> {
+        elements.iter().enumerate().map(|(i, el)| {
+            if Some(i) == inserted { None } else { Some(el.get_frame()) }
+        }).collect()
+    }
+
+    /// starts moving the elements from `from` to `to`. Elements which are still
+    /// fading in or out continue from where they are.
+    pub fn start(&mut self, from: Vec<Option<Frame<i32>>>, to: Vec<Frame<i32>>) {
+        let s = if self.running { self.progress() } else { 1.0 };
+
+        let fade_from: Vec<f32> = from.iter().enumerate().map(|(i, frame)| match *frame {
+            Some(_) => self.opacity_at(i, s),
+            None => 0.0,
+        }).collect();
+        self.fade_from = fade_from;
+
+        for &mut (_, ref mut frame, ref mut opacity) in &mut self.leaving {
+            *frame = lerp_frame(*frame, collapsed(*frame), s.min(1.0));
+            *opacity = clamp_opacity(*opacity * (1.0 - s as f32));
+        }
+
+        self.from = from;
+        self.to = to;
+        self.start_time = self.time_source.now_ns();
+        self.running = true;
+    }
+
+    /// keeps track of an element inserted at `index`, call before `start`.
+    pub fn element_inserted(&mut self, index: usize) {
+        if self.running && index <= self.fade_from.len() {
+            self.fade_from.insert(index, 0.0);
+        }
+    }
+
+    /// keeps track of the element removed at `index`, call before `start`.
+    pub fn element_removed(&mut self, index: usize) {
+        if self.running && index < self.fade_from.len() {
+            self.fade_from.remove(index);
+        }
+    }
+
+    /// current opacity of the element at `index`, below 1 while it fades in.
+    pub fn opacity(&self, index: usize) -> f32 {
+        if self.running {
+            self.opacity_at(index, self.progress())
+        } else {
+            1.0
+        }
+    }
+
+    fn opacity_at(&self, index: usize, s: f64) -> f32 {
+        match self.fade_from.get(index) {
+            Some(&a) => clamp_opacity(a + (1.0 - a) * s as f32),
+            None => 1.0,
+        }
+    }
+
+    /// new targets without restarting, e.g. after a resize.
+    pub fn retarget(&mut self, to: Vec<Frame<i32>>) {
+        self.to = to;
+    }
+
+    /// lets a removed element shrink and fade out of its current frame,
+    /// starting at its current `opacity`.
+    pub fn dismiss(&mut self, element: Box<Element>, opacity: f32) {
+        let frame = element.get_frame();
+        self.leaving.push((element, frame, opacity));
+    }
+
+    // ms since the start
+    fn elapsed(&self) -> f64 {
+        self.time_source.now_ns().saturating_sub(self.start_time) as f64 * 1e-6
+    }
+
+    // eased, may leave [0,1] with overshooting easings
+    fn progress_at(&self, t: f64) -> f64 {
+        if t >= self.duration { 1.0 } else { self.easing.at(t, self.duration) }
+    }
+
+    fn progress(&self) -> f64 {
+        self.progress_at(self.elapsed())
+    }
+
+    /// sets the interpolated frames. Called on every Update.
+    pub fn apply(&mut self, elements: &mut [Box<Element>], window_center: Vec2<i32>) {
+        if !self.running { return }
+        let t = self.elapsed();
+        // by time, the eased value can pass 1 before the end
+        let done = t >= self.duration;
+        let s = self.progress_at(t);
+
+        for (i, el) in elements.iter_mut().enumerate() {
+            let to = match self.to.get(i) {
+                Some(to) => *to,
+                None => continue,
+            };
+            match self.from.get(i) {
+                Some(&Some(from)) => el.set_frame(lerp_frame(from, to, s), window_center),
+                _ => el.set_frame(lerp_frame(collapsed(to), to, s), window_center),
+            }
+            if self.fade_from.get(i).map_or(false, |&a| a < 1.0) {
+                el.set_opacity(if done { 1.0 } else { self.opacity_at(i, s) });
+            }
+        }
+
+        for &mut (ref mut el, frame, opacity) in &mut self.leaving {
+            el.set_frame(lerp_frame(frame, collapsed(frame), s.min(1.0)), window_center);
+            el.set_opacity(clamp_opacity(opacity * (1.0 - s as f32)));
+        }
+
+        if done {
+            self.running = false;
+            for el in &mut self.leaving {
+                el.0.stop();
+            }
+            self.leaving.clear();
+            self.from.clear();
+            self.fade_from.clear();
+        }
+    }
+
+    pub fn build_window(&self, ui: &mut conrod::UiCell, ressources: &WindowRessources) {
+        for &(ref el, _, _) in &self.leaving {
+            el.build_window(ui, ressources);
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use elements::action::tests::Probe;
+
+    fn frame(x: i32, y: i32) -> Frame<i32> {
+        Frame { p0: Vec2{ x, y }, p1: Vec2{ x: x + 100, y: y + 100 } }
+    }
+
+    #[test]
+    fn overshooting_easing_runs_until_the_end() {
+        let time = ManualTime::new();
+        let mut transition = LayoutTransition::new(100.0, Easing::BackOut)
+            .with_time_source(Arc::new(time.clone()));
+        let (staying, log_staying) = Probe::new();
+        let (leaving, log_leaving) = Probe::new();
+        let mut elements: Vec<Box<Element>> = vec![staying];
+
+        transition.start(vec![Some(frame(0, 0))], vec![frame(100, 0)]);
+        transition.dismiss(leaving, 1.0);
+
+        time.advance_ms(80.0);
+        assert!(Easing::BackOut.at(80.0, 100.0) > 1.0);
+        transition.apply(&mut elements, Vec2::zero());
+        assert!(transition.is_running());
+        assert_eq!(log_leaving.borrow().opacity, Some(0.0));
+        assert!(log_staying.borrow().frame.unwrap().p0.x > 100);
+
+        time.advance_ms(20.0);
+        transition.apply(&mut elements, Vec2::zero());
+        assert!(!transition.is_running());
+        assert_eq!(log_staying.borrow().frame, Some(frame(100, 0)));
+    }
+}
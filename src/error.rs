@@ -0,0 +1,82 @@
+use std::fmt;
+use std::error;
+use std::io;
+use std::path::{Path, PathBuf};
+
+
+
+
+
+
+
+
+/*
+d88888b d8888b. d8888b.  .d88b.  d8888b.
+88'     88  `8D 88  `8D .8P  Y8. 88  `8D
+88ooooo 88oobY' 88oobY' 88    88 88oobY'
+88~~~~~ 88`8b   88`8b   88    88 88`8b
+88.     88 `88. 88 `88. `8b  d8' 88 `88.
+Y88888P 88   YD 88   YD  `Y88P'  88   YD
+
+
+*/
+
+
+
+
+/// Errors while creating a window or loading ressources.
+#[derive(Debug)]
+pub enum Error {
+    /// the file or folder doesn't exist.
+    MissingFile(PathBuf),
+    /// the file exists, but can't be read.
+    Io(PathBuf, String),
+    /// the file exists, but can't be read as font or image.
+    Decode(PathBuf, String),
+    /// bytes in memory can't be read as font or image.
+    InvalidData(String),
+    /// the image could not be uploaded to the graphics card.
+    Upload(String),
+    /// the window or its renderer could not be created.
+    Window(String),
+    /// no font or image was added with this id.
+    UnknownId(String),
+}
+
+impl Error {
+    /// MissingFile if the file doesn't exist, Io otherwise.
+    pub fn io(path: &Path, e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::NotFound => Error::MissingFile(path.to_path_buf()),
+            _ => Error::Io(path.to_path_buf(), format!("{}", e)),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::MissingFile(ref path) => write!(f, "file not found: {}", path.display()),
+            Error::Io(ref path, ref msg) => write!(f, "could not read {}: {}", path.display(), msg),
+            Error::Decode(ref path, ref msg) => write!(f, "could not decode {}: {}", path.display(), msg),
+            Error::InvalidData(ref msg) => write!(f, "could not decode data: {}", msg),
+            Error::Upload(ref msg) => write!(f, "could not upload texture: {}", msg),
+            Error::Window(ref msg) => write!(f, "could not create window: {}", msg),
+            Error::UnknownId(ref id) => write!(f, "no font or image with the id \"{}\"", id),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::MissingFile(_) => "file not found",
+            Error::Io(..) => "could not read file",
+            Error::Decode(..) => "could not decode file",
+            Error::InvalidData(_) => "could not decode data",
+            Error::Upload(_) => "could not upload texture",
+            Error::Window(_) => "could not create window",
+            Error::UnknownId(_) => "unknown font or image id",
+        }
+    }
+}
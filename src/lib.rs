@@ -6,16 +6,25 @@
 
 pub mod elements;
 pub mod composites;
+pub mod clock;
+pub mod error;
 
 #[macro_use] extern crate conrod;
 extern crate time;
 extern crate num;
 extern crate find_folder;
 extern crate image;
+extern crate gif;
+extern crate toml;
+#[cfg(feature = "svg")]
+extern crate nsvg;
 
 use composites::*;
-use elements::{*, container::*, basic::*, action::*, shared::*, structures::*};
+use clock::*;
+use elements::{*, container::*, basic::*, action::*, shared::*, structures::*, plot::*, canvas::*, easing::*, timeline::*, spring::*, sprite::*, animated_image::*, theme::*};
 use std::sync::mpsc::{self, Sender, Receiver};
+use std::rc::Rc;
+use std::cell::Cell;
 
 
 
@@ -95,6 +104,321 @@ impl Timer {
 
 
 
+/*
+d88888b db    db    .d888b.
+88'     `8b  d8'    88' `8D
+88ooooo  `8bd8'     `V8o88'
+88~~~~~  .dPYb.        d8'
+88.     .8P  Y8.      d8'
+Y88888P YP    YP     d8'
+
+
+*/
+
+
+
+pub fn example9() {
+
+    let (sender, receiver): (Sender<ActionMsg>, Receiver<ActionMsg>) = mpsc::channel();
+
+    // construct window
+    let mut window = Window::new("Spring Test".to_string(), 800,800).unwrap();
+    let font = Font::from_handle(&window.default_font(), 42, conrod::color::BLACK);
+    window.add_receiver(receiver);
+
+    let pad = Pad::new(
+        Button::new()
+            .with_font(font.write("drag me".to_string()))
+            .with_id("Drag".to_string())
+            .with_sender(sender.clone()),
+        PadAlignment::Center,
+        PadElementSize::Positive(Dim::Absolute(250), Dim::Absolute(120))
+    );
+
+    // follows the mouse while dragged and springs back when released
+    let dragging = Rc::new(Cell::new(false));
+    let spring = Socket::new(
+        SpringAnimation::new(pad, Spring::wobbly())
+            .with_floating(true)
+    ).with_action_receive(Box::new(move |spring: &mut SpringAnimation, msg: ActionMsg|{
+        match msg.msg {
+            ActionMsgData::MousePressLeft(x,y) => {
+                dragging.set(spring.get_frame().inside(x as i32, y as i32));
+            },
+            ActionMsgData::MouseDragLeft(dx,dy) => {
+                if dragging.get() {
+                    spring.set_position(dx, dy);
+                }
+            },
+            ActionMsgData::MouseReleaseLeft(_,_) => {
+                if dragging.get() {
+                    dragging.set(false);
+                    spring.snap_back();
+                }
+            },
+            _ => ()
+        }
+    }));
+
+    window.add_element(spring);
+    window.run();
+}
+
+
+
+
+
+/*
+d88888b db    db    .d888b.
+88'     `8b  d8'    88   8D
+88ooooo  `8bd8'     `VoooY'
+88~~~~~  .dPYb.     .d~~~b.
+88.     .8P  Y8.    88   8D
+Y88888P YP    YP    `Y888P'
+
+
+*/
+
+
+pub fn example8() {
+
+    let (sender, receiver): (Sender<ActionMsg>, Receiver<ActionMsg>) = mpsc::channel();
+
+    // construct window
+    let mut window = Window::new("Clock Test".to_string(), 1200,600).unwrap();
+    let font = Font::new("NotoSans-Regular".to_string(), 42, conrod::color::BLACK);
+    window.add_receiver(receiver);
+
+    // keeps the clocks moving without any input, stopped by the window's Exit
+    let (timer_sender, timer_receiver): (Sender<ActionMsg>, Receiver<ActionMsg>) = mpsc::channel();
+    window.add_sender(timer_sender);
+    let timer = Timer::new(sender.clone(), timer_receiver, 30.0);
+
+    let mut list = List::new(ListAlignment::Horizontal);
+
+    // time of day
+    list.push(Clock::new(ClockFace::Analog, ClockMode::Time));
+
+    // stopwatch, controlled by the buttons below
+    let mut stopwatch = List::new(ListAlignment::Vertical);
+    stopwatch.push(Socket::new(
+        Clock::new(ClockFace::Digital, ClockMode::Stopwatch)
+            .with_id("Stopwatch".to_string())
+            .with_sender(sender.clone())
+    ).with_action_receive(Box::new(|clock: &mut Clock, msg: ActionMsg|{
+        match (msg.sender_id.as_str(), msg.msg) {
+            ("Start", ActionMsgData::Click) =>
+                if clock.is_running() { clock.pause() } else { clock.start() },
+            ("Lap", ActionMsgData::Click) => { clock.lap(); },
+            ("Reset", ActionMsgData::Click) => clock.reset(),
+            ("Stopwatch", ActionMsgData::Lap(n, t)) => println!("lap {}: {:.2}s", n + 1, t),
+            ("Countdown", ActionMsgData::Finished) => println!("countdown finished"),
+            _ => ()
+        }
+    })));
+
+    let mut buttons = List::new(ListAlignment::Horizontal);
+    for s in ["Start", "Lap", "Reset"].iter() {
+        buttons.push(Pad::new(
+            Button::new()
+                .with_font(font.write(s.to_string()))
+                .with_id(s.to_string())
+                .with_sender(sender.clone()),
+            PadAlignment::Center,
+            PadElementSize::Negative(Dim::Absolute(10), Dim::Absolute(10))
+        ));
+    }
+    stopwatch.push(buttons);
+    list.push(stopwatch);
+
+    // countdown, started right away
+    let mut countdown = Clock::new(ClockFace::Analog, ClockMode::Countdown(90.0))
+        .with_id("Countdown".to_string())
+        .with_sender(sender.clone());
+    countdown.start();
+    list.push(countdown);
+
+    window.add_element(list);
+    window.run();
+    timer.stop();
+}
+
+
+
+
+
+/*
+d88888b db    db    d88888D
+88'     `8b  d8'    VP  d8'
+88ooooo  `8bd8'        d8'
+88~~~~~  .dPYb.       d8'
+88.     .8P  Y8.     d8'
+Y88888P YP    YP    d8'
+
+
+*/
+
+
+pub fn example7() {
+
+    let (_sender, receiver): (Sender<ActionMsg>, Receiver<ActionMsg>) = mpsc::channel();
+
+    // construct window
+    let mut window = Window::new("Canvas Test".to_string(), 800,800).unwrap();
+    let font = Font::new("NotoSans-Regular".to_string(), 24, conrod::color::BLACK);
+    window.add_receiver(receiver);
+
+    let v = |x,y| Vec2{x,y};
+
+    // local coordinates from (0,0) to (100,100), independent of the frame
+    let canvas = Canvas::new()
+        .with_view(Frame{ p0: v(0.0, 0.0), p1: v(100.0, 100.0) })
+        .with_background(conrod::color::WHITE)
+        .with_shape(Shape::RoundedRectangle(
+            Frame{ p0: v(5.0, 5.0), p1: v(95.0, 95.0) }, 5.0,
+            ShapeStyle::stroke(conrod::color::DARK_GREY, 2.0)
+        ))
+        .with_shape(Shape::Circle(v(30.0, 70.0), 10.0,
+            ShapeStyle::fill(conrod::color::LIGHT_BLUE).with_stroke(conrod::color::BLUE, 2.0)))
+        .with_shape(Shape::Ellipse(v(70.0, 70.0), 30.0, 15.0,
+            ShapeStyle::fill(conrod::color::LIGHT_GREEN)))
+        .with_shape(Shape::Arc(v(30.0, 30.0), 15.0, 0.0, 4.5,
+            ShapeStyle::fill(conrod::color::LIGHT_ORANGE).with_stroke(conrod::color::ORANGE, 2.0)))
+        .with_shape(Shape::Polygon(vec![v(60.0, 15.0), v(85.0, 15.0), v(72.5, 40.0)],
+            ShapeStyle::fill(conrod::color::LIGHT_RED).with_stroke(conrod::color::RED, 1.0)))
+        .with_shape(Shape::Polyline(vec![v(10.0, 50.0), v(40.0, 55.0), v(60.0, 45.0), v(90.0, 50.0)],
+            ShapeStyle::stroke(conrod::color::BLACK, 3.0)))
+        .with_shape(Shape::Text(v(50.0, 90.0), font.write("click to draw".to_string())));
+
+    // draw a dot where the user clicks
+    let socket = Socket::new(canvas)
+        .with_action_receive(Box::new(|canvas: &mut Canvas, msg: ActionMsg|{
+            match msg.msg {
+                ActionMsgData::MousePressLeft(x,y) => {
+                    let frame = canvas.get_frame();
+                    if frame.inside(x as i32, y as i32) {
+                        let p = canvas.to_local(x, y);
+                        canvas.push(Shape::Circle(p, 1.5, ShapeStyle::fill(conrod::color::PURPLE)));
+                    }
+                },
+                _ => ()
+            }
+        }));
+
+    window.add_element(Pad::new(
+        socket,
+        PadAlignment::Center,
+        PadElementSize::Negative(Dim::Absolute(25), Dim::Absolute(25))
+    ));
+    window.run();
+}
+
+
+
+
+
+/*
+d88888b db    db       dD
+88'     `8b  d8'      d8'
+88ooooo  `8bd8'      d8'
+88~~~~~  .dPYb.     d8888b.
+88.     .8P  Y8.    88' `8D
+Y88888P YP    YP    `8888P
+
+
+*/
+
+
+pub fn example6() {
+
+    let (sender, receiver): (Sender<ActionMsg>, Receiver<ActionMsg>) = mpsc::channel();
+
+    // construct window
+    let mut window = Window::new("Plot Test".to_string(), 800,800).unwrap();
+    window.add_receiver(receiver);
+
+    // font for plots exported with a right click
+    let assets = find_folder::Search::KidsThenParents(3, 5).for_folder("assets").unwrap();
+    let export_font = conrod::text::font::from_file(
+        assets.join("fonts/NotoSans/NotoSans-Regular.ttf")
+    ).unwrap();
+
+    let mut list = List::new(ListAlignment::Vertical);
+
+    // exponential growth on a log10 axis
+    let mut growth = Series::new("growth".to_string(), conrod::color::RED);
+    for i in 0..100 {
+        let x = i as f64 * 0.1;
+        growth.push(x, (1.5 * x).exp());
+    }
+    let mut decay = Series::new("decay".to_string(), conrod::color::DARK_BLUE);
+    for i in 0..100 {
+        let x = i as f64 * 0.1;
+        decay.push(x, 1e4 * (-x).exp());
+    }
+    list.push(
+        Socket::new(
+            Plot::new()
+                .with_series(growth)
+                .with_series(decay)
+                .with_x_axis(Axis::new(AxisScale::Linear).with_label("t".to_string()))
+                .with_y_axis(Axis::new(AxisScale::Log10).with_label("N".to_string()))
+                .with_legend(PadAlignment::TopLeft)
+                .with_crosshair(true)
+                .with_annotation(Annotation::HorizontalLine(100.0, conrod::color::GREY))
+                .with_annotation(Annotation::Arrow(2.0, 1e5, 3.07, 100.0, "equal".to_string()))
+                .with_export(std::path::PathBuf::from("growth"), 1600, 1200)
+                .with_export_font(export_font)
+                .with_id("Growth".to_string())
+                .with_sender(sender.clone())
+        ).with_action_receive(Box::new(|_,msg|{
+            match (msg.sender_id.as_ref(), msg.msg) {
+                ("Growth", ActionMsgData::PlotHover(name, x, y)) => {
+                    println!("{}: {} {}", name, x, y);
+                },
+                ("Growth", ActionMsgData::Text(path)) => {
+                    println!("exported {}", path);
+                },
+                _ => ()
+            }
+        }))
+    );
+
+    // measurement over one day on a time axis
+    let t0 = 1514764800.0;
+    let mut temperature = Series::new("temperature".to_string(), conrod::color::BLUE);
+    for i in 0..(24*6) {
+        let t = i as f64 * 600.0;
+        use std::f64;
+        temperature.push(t0 + t, 20.0 + 5.0 * (t / 86400.0 * 2.0 * f64::consts::PI).sin());
+    }
+    list.push(
+        Plot::new()
+            .with_series(temperature)
+            .with_x_axis(Axis::new(AxisScale::Time))
+            .with_y_axis(Axis::new(AxisScale::Linear).with_label("°C".to_string()))
+    );
+
+    // categories
+    let names: Vec<String> = vec!["Mon", "Tue", "Wed", "Thu", "Fri"].into_iter()
+        .map(|s| s.to_string())
+        .collect();
+    list.push(
+        Plot::new()
+            .with_series(Series::new("sales".to_string(), conrod::color::DARK_GREEN)
+                .with_points(vec![(0.0, 3.0), (1.0, 7.0), (2.0, 4.0), (3.0, 9.0), (4.0, 6.0)]))
+            .with_x_axis(Axis::new(AxisScale::Categorical(names)))
+            .with_y_axis(Axis::new(AxisScale::SymLog(1.0)))
+    );
+
+    window.add_element(list);
+    window.run();
+}
+
+
+
+
+
 /*
 d88888b db    db        ooooo
 88'     `8b  d8'       8P~~~~
@@ -110,24 +434,24 @@ Y88888P YP    YP      88oobY'
 pub fn example5() {
 
     let (sender, receiver): (Sender<ActionMsg>, Receiver<ActionMsg>) = mpsc::channel();
-    let assets = find_folder::Search::KidsThenParents(3, 5).for_folder("assets").unwrap();
 
     // construct window
-    let mut window = Window::new("Scroll Test".to_string(), 800,800);
+    let mut window = Window::new("Scroll Test".to_string(), 800,800).unwrap();
     let font = Font::new("NotoSans-Regular".to_string(), 42, conrod::color::BLACK);
     window.add_receiver(receiver);
 
-    window.add_image(
+    // embedded in the binary, no assets folder needed
+    let fan = window.add_image_from_bytes(
         "JapaneseFan".to_string(),
-        &assets.join("images/japanese-fan.png")
-    );
+        include_bytes!("../assets/images/japanese-fan.png")
+    ).unwrap();
 
     let mut layers = Layers::new();
 
     // * layer 0 ---------------------------------------------------------
 
     let plane = Plane::new(Graphic::Texture(
-        Texture::new("JapaneseFan".to_string())
+        Texture::from_handle(&fan)
             .with_mode(TextureMode::FitMax)
     ));
     layers.push(plane);
@@ -135,11 +459,12 @@ pub fn example5() {
     // * layer 1 ---------------------------------------------------------
 
 
+    // springs back when dragged beyond the ends
     let mut scroll = Scroll::new(
         ScrollAlignment::Vertical,
         "Scroll".to_string(),
         sender.clone()
-    );
+    ).with_bounce(Spring::default());
 
     for i in 0..10 {
         let s = format!("Button {}",i);
@@ -198,10 +523,11 @@ pub fn example4() {
     let assets = find_folder::Search::KidsThenParents(3, 5).for_folder("assets").unwrap();
 
     // construct window
-    let mut window = Window::new("Animation Test".to_string(), 800,800);
+    let mut window = Window::new("Animation Test".to_string(), 800,800).unwrap();
     window.add_receiver(receiver);
 
-    window.add_image(
+    // decoded in the background, a gray plane is shown until it's ready
+    let fan = window.add_image_async(
         "JapaneseFan".to_string(),
         &assets.join("images/japanese-fan.png")
     );
@@ -209,8 +535,9 @@ pub fn example4() {
     let mut layers = Layers::new();
 
     let plane = Plane::new(Graphic::Texture(
-        Texture::new("JapaneseFan".to_string())
+        Texture::from_handle(&fan)
             .with_mode(TextureMode::FitMax)
+            .with_placeholder(Graphic::Color(conrod::color::LIGHT_GRAY))
     ));
 
     let pad = Pad::new(
@@ -269,30 +596,20 @@ Y88888P YP    YP      Y8888P'
 
 pub fn example3() {
 
-    pub struct AnimClick;
-    impl SizeAnimation for AnimClick {
-        fn calc(&self, t: f64, duration: f64) -> (Dim, Dim) {
-            let rel = t/duration;
-            use std::f64;
-            let tau = rel * f64::consts::PI;
-            let fx = Dim::Relative( -0.25 * tau.sin() );
-            let fy = Dim::Relative( 2.0 * tau.sin() );
-            (fx,fy)
-        }
-    }
-
     let (sender, receiver): (Sender<ActionMsg>, Receiver<ActionMsg>) = mpsc::channel();
 
-    // setup timer for continuous refresh of window
-    let (timer_sender, timer_receiver): (Sender<ActionMsg>, Receiver<ActionMsg>)
-        = mpsc::channel();
-    let _timer = Timer::new(sender.clone(), timer_receiver, 120.0);
-
     // construct window
-    let mut window = Window::new("Animation Test".to_string(), 800,800);
-    let font = Font::new("NotoSans-Regular".to_string(), 42, conrod::color::BLACK);
+    let mut window = Window::new("Animation Test".to_string(), 800,800).unwrap();
+    // follows the theme, switched by the first button
+    let font = Font::themed();
+    window.set_theme(Theme::light());
     window.add_receiver(receiver);
-    window.add_sender(timer_sender);
+
+    let assets = find_folder::Search::KidsThenParents(3, 5).for_folder("assets").unwrap();
+    if let Err(e) = window.load_stylesheet(&assets.join("styles/example.toml")) {
+        println!("{}", e);
+    }
+    window.set_hot_reload(true);
 
     let mut scroll = Scroll::new(
         ScrollAlignment::Vertical,
@@ -300,11 +617,37 @@ pub fn example3() {
         sender.clone()
     );
 
+    let dark = Rc::new(Cell::new(false));
+    let theme_sender = sender.clone();
+    let theme_button = Socket::new(Button::new()
+            .with_font(font.write("Dark / Light".to_string()))
+            .with_sender(sender.clone())
+            .with_id("Theme".to_string()))
+        .with_action_receive(Box::new(move |_, amsg: ActionMsg|{
+            if amsg.msg == ActionMsgData::Click && amsg.sender_id == "Theme" {
+                dark.set(!dark.get());
+                let theme = if dark.get() { Theme::dark() } else { Theme::light() };
+                let _ = theme_sender.send(ActionMsg{
+                    sender_id: "Theme".to_string(),
+                    msg: ActionMsgData::Theme(theme),
+                });
+            }
+        }));
+    let mut pad = Pad::new(
+        theme_button,
+        PadAlignment::Center,
+        PadElementSize::Negative(Dim::Absolute(25),Dim::Absolute(25))
+    );
+    pad.set_min_size(Vec2{x: 200, y: 100});
+    scroll.push(pad);
+
     for i in 0..10 {
         let s = format!("Button {}", i);
 
+        let class = if i % 2 == 0 { "even" } else { "odd" };
         let button = Button::new()
             .with_font(font.write(s.clone()))
+            .with_class(class.to_string())
             .with_sender(sender.clone())
             .with_id(s.clone());
         
@@ -315,13 +658,18 @@ pub fn example3() {
         );
         pad.set_min_size(Vec2{x: 200, y: 100});
 
-        let animation = Animation::new(pad)
-            .with_duration(500.0)
-            .with_size_animation(Box::new(AnimClick))
+        // every button has its own clock, so only the clicked one moves
+        let timeline = Timeline::new(pad, Clip::Size(500.0, Box::new(
+                Tween::new(
+                    (Dim::Relative(0.0), Dim::Relative(0.0)),
+                    (Dim::Relative(-0.25), Dim::Relative(2.0)),
+                    Easing::QuadOut
+                ).with_there_and_back(true)
+            )))
             .with_floating(true);
 
-        let socket = Socket::new(animation)
-            .with_action_receive(Box::new(move |ani: &mut Animation, amsg: ActionMsg|{   
+        let socket = Socket::new(timeline)
+            .with_action_receive(Box::new(move |ani: &mut Timeline, amsg: ActionMsg|{   
                 if amsg.msg == ActionMsgData::Click && amsg.sender_id == s {
                     println!("{}",s);
                     ani.start();
@@ -366,75 +714,51 @@ Y88888P YP    YP      888888D
 
 pub fn expample2() {
 
-
-    pub struct AnimClick;
-    impl SizeAnimation for AnimClick {
-        fn calc(&self, t: f64, duration: f64) -> (Dim, Dim) {
-            let rel = t/duration;
-            use std::f64;
-            let tau = rel * f64::consts::PI;
-            let f = Dim::Relative( -0.25 * tau.sin() );
-            (f,f)
-        }
-    }
-    impl PositionAnimation for AnimClick {
-        fn calc(&self, t: f64, duration: f64) -> (Dim, Dim) {
-            let rel = t/duration;
-            use std::f64;
-            let tau = rel * f64::consts::PI * 2.0;
-            (
-                Dim::Absolute( 0 ),
-                Dim::Absolute( (10.0 * tau.sin()) as i32 )
-            )
-        }
-    }
-
-
     let (sender, receiver): (Sender<ActionMsg>, Receiver<ActionMsg>) = mpsc::channel();
 
-    // setup timer for continuous refresh of window
-    let (timer_sender, timer_receiver): (Sender<ActionMsg>, Receiver<ActionMsg>)
-        = mpsc::channel();
-    let _timer = Timer::new(sender.clone(), timer_receiver, 120.0);
-
     // construct window
-    let mut window = Window::new("Animation Test".to_string(), 800,800);
+    let mut window = Window::new("Animation Test".to_string(), 800,800).unwrap();
     let font = Font::new("NotoSans-Regular".to_string(), 42, conrod::color::BLACK);
     window.add_receiver(receiver);
-    window.add_sender(timer_sender);
 
     let assets = find_folder::Search::KidsThenParents(3, 5).for_folder("assets").unwrap();
 
-    window.add_font(
+    // a missing ressource doesn't have to end the application
+    if let Err(e) = window.add_font(
         "NotoSans-Italic".to_string(),
         &assets.join("fonts/NotoSans/NotoSans-Italic.ttf")
-    );
+    ) {
+        println!("{}", e);
+    }
     let _font_italic = Font::new("NotoSans-Italic".to_string(), 42, conrod::color::BLACK);
     window.add_font(
         "NotoSans-Bold".to_string(),
         &assets.join("fonts/NotoSans/NotoSans-Bold.ttf")
-    );
+    ).unwrap();
     window.add_font(
         "NotoSans-BoldItalic".to_string(),
         &assets.join("fonts/NotoSans/NotoSans-BoldItalic.ttf")
-    );
+    ).unwrap();
+
+    // edit the images while the example is running
+    window.set_hot_reload(true);
 
-    window.add_image(
+    let rust_logo = window.add_image(
         "RustLogo_hover".to_string(),
         &assets.join("images/rust_hover.png")
-    );
+    ).unwrap();
 
-    window.add_image(
+    let fan = window.add_image(
         "JapaneseFan".to_string(),
         &assets.join("images/japanese-fan.png")
-    );
+    ).unwrap();
 
     // add elements to window
 
     let mut layers = Layers::new();
 
     layers.push(Plane::new(Graphic::Texture(
-        Texture::new("JapaneseFan".to_string())
+        Texture::from_handle(&fan)
             .with_mode(TextureMode::FitMax)
     )));
 
@@ -442,7 +766,7 @@ pub fn expample2() {
 
     let pad = Pad::new(Button::new()
         .with_graphic(Graphic::Texture(
-            Texture::new("RustLogo_hover".to_string())
+            Texture::from_handle(&rust_logo)
             ))
         .with_font(font.write("Press".to_string()))
         .with_id("testbutton".to_string())
@@ -451,20 +775,24 @@ pub fn expample2() {
         PadElementSize::Positive(Dim::Absolute(200),Dim::Absolute(200))
     );
 
-    #[allow(unused_doc_comment)]
-    /** first the long animations, then the short ones.
-     * Otherwise, you could get flickering due to reset of
-     * inner animations while outer ones still running.
-     **/
-    let animation = Animation::new(pad)
-        .with_duration(500.0)
-        .with_size_animation(Box::new(AnimClick));
-
-    let animation2 = Animation::new(animation)
-        .with_duration(375.0)
-        .with_position_animation(Box::new(AnimClick));
-
-    let socket = Socket::new(animation2)
+    // shrink and wobble at the same time, driven by one clock
+    let timeline = Timeline::new(pad, Clip::Parallel(vec![
+        Clip::Size(500.0, Box::new(
+            Tween::new(
+                (Dim::Relative(0.0), Dim::Relative(0.0)),
+                (Dim::Relative(-0.25), Dim::Relative(-0.25)),
+                Easing::QuadOut
+            ).with_there_and_back(true)
+        )),
+        Clip::position_keyframes(
+            Keyframes::new((Dim::Absolute(0), Dim::Absolute(0)))
+                .key( 94.0, (Dim::Absolute(0), Dim::Absolute( 10)), Easing::QuadOut)
+                .key(281.0, (Dim::Absolute(0), Dim::Absolute(-10)), Easing::QuadInOut)
+                .key(375.0, (Dim::Absolute(0), Dim::Absolute(  0)), Easing::QuadIn)
+        ),
+    ]));
+
+    let socket = Socket::new(timeline)
         .with_action_receive(Box::new(|ani, amsg|{
             match (amsg.sender_id.as_ref(), amsg.msg) {
                 ("testbutton", ActionMsgData::Click) => {
@@ -497,14 +825,16 @@ Y88888P YP    YP
 
 pub fn example() {
 
-    let mut window = Window::new("Container".to_string(), 800, 800);
+    let mut window = Window::new("Container".to_string(), 800, 800).unwrap();
     let mut font = Font::new("NotoSans-Regular".to_string(), 42, conrod::color::BLACK);
     let (base_sender, base_receiver): (Sender<ActionMsg>, Receiver<ActionMsg>) = mpsc::channel();
     window.add_receiver(base_receiver);
 
     let mut layers = Layers::new();
 
-    let mut list = List::new(ListAlignment::Vertical);
+    // elements slide, grow in and shrink out when the list changes
+    let mut list = List::new(ListAlignment::Vertical)
+        .with_transition(300.0, Easing::CubicOut);
 
     let mut sublist = List::new(ListAlignment::Horizontal);
 
@@ -582,7 +912,10 @@ pub fn example() {
             .with_action_receive(Box::new(move |list: &mut List, msg: ActionMsg|{
                 match (msg.sender_id.as_ref(), msg.msg) {
                     ("Delete", ActionMsgData::Click) => {
-                        let _ = list.pop();
+                        if !list.is_empty() {
+                            let last = list.len() - 1;
+                            list.dismiss(last);
+                        }
                     },
                     ("Add", ActionMsgData::Click) => {
                         list.push(Text::new(tmp.write("one more time!".to_string())))